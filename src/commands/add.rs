@@ -0,0 +1,251 @@
+//! Command implementation for adding a dependency to a Magnet.toml file
+
+use crate::MagnetError;
+use crate::commands::generate::{GenerateOptions, generate};
+use crate::commands::utils::find_package;
+use crate::document::{insert_entry, parse_document, table_mut};
+use crate::manager::ManifestManager;
+use crate::models::{DependencyKind, PackageModel};
+use crate::utils::diff_path;
+use eyre::{Context, Result, bail};
+use std::fs;
+use std::path::PathBuf;
+use toml_edit::{Array, InlineTable, Item, Value};
+use tracing::{info, warn};
+
+/// Keys selecting where a dependency comes from; setting one replaces the others
+const SOURCE_KEYS: &[&str] = &[
+    "nexus",
+    "workspace",
+    "path",
+    "git",
+    "branch",
+    "tag",
+    "rev",
+    "registry",
+];
+
+/// Where an added dependency comes from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum AddSource {
+    /// The registry, at the given version
+    #[default]
+    Registry,
+    /// A package of the nexus (`nexus = true`)
+    Nexus,
+    /// The shared definition of the workspace (`workspace = true`)
+    Workspace,
+    /// A local directory, relative to the current directory
+    Path(PathBuf),
+    /// A git repository
+    Git(String),
+}
+
+/// Configuration options for the add command
+pub struct AddOptions {
+    /// Directory the command runs in; the package is looked up from here
+    pub path: PathBuf,
+    /// Name of the dependency
+    pub name: String,
+    /// Version requirement of the dependency
+    pub version: Option<String>,
+    /// Where the dependency comes from
+    pub source: AddSource,
+    /// Dependency table to add the dependency to
+    pub kind: DependencyKind,
+    /// Features to enable
+    pub features: Vec<String>,
+    /// Package to add the dependency to, instead of the package in `path`
+    pub package: Option<String>,
+    /// Run generate for the workspace of the package afterwards
+    pub generate: bool,
+}
+
+impl Default for AddOptions {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("."),
+            name: String::new(),
+            version: None,
+            source: AddSource::default(),
+            kind: DependencyKind::Normal,
+            features: Vec::new(),
+            package: None,
+            generate: false,
+        }
+    }
+}
+
+/// Add a dependency to the Magnet.toml of a package, keeping its formatting
+pub fn add(options: &AddOptions) -> Result<()> {
+    let mut manager = ManifestManager::from_dir(&options.path)?;
+    let package = find_package(&mut manager, &options.path, options.package.as_deref())?;
+    let entry = dependency_entry(&mut manager, &package, options)?;
+
+    let content = fs::read_to_string(&package.source_path)
+        .with_context(|| format!("Failed to read {}", package.source_path.display()))?;
+    let mut document = parse_document(&content)?;
+    let table = table_mut(&mut document, options.kind.table_name())?;
+
+    let entry = merge_entry(table.get(&options.name), entry);
+    insert_entry(table, &options.name, entry);
+    fs::write(&package.source_path, document.to_string())
+        .with_context(|| format!("Failed to write {}", package.source_path.display()))?;
+    info!(
+        "Added {} to [{}] of {}",
+        options.name,
+        options.kind.table_name(),
+        package.source_path.display()
+    );
+
+    if options.generate {
+        let workspace = manager
+            .index()?
+            .find_workspace_containing(&package.root_path)
+            .cloned();
+        match workspace {
+            Some(workspace) => generate(&GenerateOptions {
+                config_path: workspace.root_path,
+                ..Default::default()
+            })?,
+            None => warn!(
+                "Package {} is not part of a workspace, there is nothing to generate",
+                package.name
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// The TOML value of the new dependency, after checking that its source exists
+fn dependency_entry(
+    manager: &mut ManifestManager,
+    package: &PackageModel,
+    options: &AddOptions,
+) -> Result<InlineTable> {
+    let name = &options.name;
+    let mut entry = InlineTable::new();
+    if let Some(version) = &options.version {
+        entry.insert("version", version.as_str().into());
+    }
+
+    match &options.source {
+        AddSource::Registry => {
+            if options.version.is_none() {
+                bail!(
+                    "Specify a version with {}@<version>, or a source with --nexus, --workspace, --path or --git",
+                    name
+                );
+            }
+        }
+        AddSource::Nexus => {
+            if manager.index()?.find_packages(name).is_empty() {
+                bail!(MagnetError::UnresolvedDependency {
+                    name: name.clone(),
+                    message: format!(
+                        "No package named '{}' found in the nexus at {}",
+                        name,
+                        manager.root_path.display()
+                    ),
+                });
+            }
+            entry.insert("nexus", true.into());
+        }
+        AddSource::Workspace => {
+            let workspace = manager
+                .index()?
+                .find_workspace_containing(&package.root_path)
+                .cloned();
+            let defined = workspace.as_ref().is_some_and(|workspace| {
+                manager.workspace_dependencies(workspace).contains_key(name)
+            });
+            if !defined {
+                bail!(MagnetError::UnresolvedDependency {
+                    name: name.clone(),
+                    message: format!(
+                        "'{}' is not defined in the [workspace.dependencies] of package {} or in the [dependencies] of its nexus",
+                        name, package.name
+                    ),
+                });
+            }
+            entry.insert("workspace", true.into());
+        }
+        AddSource::Path(path) => {
+            let path = path
+                .canonicalize()
+                .map_err(|_| MagnetError::ManifestNotFound { path: path.clone() })?;
+            if !path.join("Magnet.toml").exists() && !path.join("Cargo.toml").exists() {
+                bail!(MagnetError::ManifestNotFound { path });
+            }
+            let relative = diff_path(&package.root_path, &path);
+            entry.insert("path", relative.to_string_lossy().replace('\\', "/").into());
+        }
+        AddSource::Git(url) => {
+            entry.insert("git", url.as_str().into());
+        }
+    }
+
+    if !options.features.is_empty() {
+        let features: Array = options.features.iter().map(String::as_str).collect();
+        entry.insert("features", Value::Array(features));
+    }
+
+    Ok(entry)
+}
+
+/// Combine the keys set by the command with an existing entry, like `cargo add` does: other
+/// keys such as `optional` or `default-features` are kept, features are added to the
+/// enabled ones, and a new source replaces the previous one
+fn merge_entry(existing: Option<&Item>, update: InlineTable) -> Value {
+    let mut entry = match existing {
+        Some(Item::Value(Value::InlineTable(table))) => table.clone(),
+        Some(Item::Value(Value::String(version))) => {
+            let mut table = InlineTable::new();
+            table.insert("version", Value::String(version.clone()));
+            table
+        }
+        Some(Item::Table(table)) => table.clone().into_inline_table(),
+        _ => InlineTable::new(),
+    };
+    let has_existing = !entry.is_empty();
+
+    if SOURCE_KEYS.iter().any(|key| update.contains_key(key)) {
+        entry.retain(|key, _| !SOURCE_KEYS.contains(&key) || update.contains_key(key));
+    }
+    // Cargo does not accept a version next to `workspace = true`
+    if update.contains_key("workspace") {
+        entry.remove("version");
+    }
+    for (key, value) in update.iter() {
+        if key != "features" {
+            entry.insert(key, value.clone());
+            continue;
+        }
+        let mut features = entry
+            .get("features")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        for feature in value.as_array().into_iter().flatten() {
+            if !features
+                .iter()
+                .any(|enabled| enabled.as_str() == feature.as_str())
+            {
+                features.push(feature.clone());
+            }
+        }
+        entry.insert("features", Value::Array(features));
+    }
+    if has_existing {
+        entry.fmt();
+    }
+
+    // A registry dependency without other keys is written as a plain version
+    if entry.len() == 1
+        && let Some(version) = entry.get("version")
+    {
+        return version.clone();
+    }
+    Value::InlineTable(entry)
+}
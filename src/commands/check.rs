@@ -1,19 +1,32 @@
 //! Command implementation for checking Magnet.toml for issues
 
 use crate::manager::ManifestManager;
-use crate::models::WorkspaceModel;
+use crate::models::{WorkspaceModel, validate_features};
 use eyre::Result;
 use std::path::Path;
-use tracing::info;
+use tracing::{error, info};
 
 /// Check command for verifying the consistency of workspace dependencies
-pub fn check(config_path: &Path) -> Result<()> {
+pub fn check(config_path: &Path, strict: bool) -> Result<()> {
     let workspace = WorkspaceModel::from_dir(config_path)?;
 
     // Create a workspace manager
-    let mut nexus_manager = ManifestManager::from_dir(&config_path)?;
+    let mut nexus_manager = ManifestManager::from_dir(config_path)?.with_strict(strict);
+    // Report every package with issues, such as local packages that do not match the
+    // declared version requirement, before failing
+    let mut errors = Vec::new();
     for mut package in workspace.list_packages()? {
-        nexus_manager.resolve_package_dependencies(&mut package)?;
+        let result = validate_features(&package)
+            .and_then(|_| nexus_manager.resolve_package_dependencies(&mut package));
+        if let Err(err) = result {
+            error!("Package {}: {}", package.name, err);
+            errors.push(err);
+        }
+    }
+    // The first error is kept in the chain, so its kind decides the exit code
+    let failed = errors.len();
+    if let Some(first) = errors.into_iter().next() {
+        return Err(first.wrap_err(format!("{} package(s) have dependency issues", failed)));
     }
 
     info!("All package dependencies are properly resolved.");
@@ -5,14 +5,21 @@
 //! It supports workspace = true and nexus = true dependencies by resolving them to paths.
 
 use crate::generator::CargoGenerator;
-use crate::manager::ManifestManager;
-use crate::models::{DependencyModel, ManifestModel, PackageModel, PatchMap, WorkspaceModel};
+use crate::manager::{ManifestManager, ResolutionMode};
+use crate::models::{
+    DependencyKind, DependencyModel, DependencyModelMap, ManifestModel, PackageModel, PatchMap,
+    WorkspaceModel,
+};
 use crate::utils::maybe_join;
 use eyre::{Context, Result};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
+
+/// Files of a root package that are not linked into the export directory
+const ROOT_PACKAGE_SKIPPED_FILES: &[&str] = &["Cargo.toml", "Magnet.toml", "Cargo.lock", "target"];
+
 // -----------------------------------------------------------------------------
 // Public API
 // -----------------------------------------------------------------------------
@@ -34,6 +41,12 @@ pub struct ExportOptions {
     pub symlink_cargo_dir: bool,
     /// Clean the export directory before exporting
     pub clean: bool,
+    /// How dependencies on exported crates are written
+    pub mode: ResolutionMode,
+    /// In publish mode, keep the `path` next to the `version`
+    pub keep_path: bool,
+    /// Fail on dependencies that cannot be resolved
+    pub strict: bool,
 }
 
 impl Default for ExportOptions {
@@ -46,6 +59,9 @@ impl Default for ExportOptions {
             include_cargo_dir: true,
             symlink_cargo_dir: true,
             clean: true,
+            mode: ResolutionMode::default(),
+            keep_path: false,
+            strict: false,
         }
     }
 }
@@ -79,11 +95,15 @@ struct Exporter {
     processed_crates: HashSet<String>,
     /// List of workspace members
     workspace_members: Vec<String>,
-    /// Nexus manager for resolving dependencies
+    /// Nexus manager for resolving dependencies. It always resolves to paths, which are
+    /// needed to find the crates.
     nexus_manager: ManifestManager,
     /// Name of the crates directory
     crates_dir_name: String,
     patch: PatchMap,
+    /// Copy of the nexus manager in the requested resolution mode, which rewrites the
+    /// dependencies on exported crates
+    output_manager: ManifestManager,
 }
 
 impl Exporter {
@@ -110,7 +130,10 @@ impl Exporter {
         let export_crates_dir = export_dir.join(&options.crates_dir);
 
         // Create nexus manager for resolving workspace and nexus dependencies
-        let nexus_manager = ManifestManager::from_dir(&options.package_path)?;
+        // Crates are always located through their paths, the mode only affects the output
+        let nexus_manager = ManifestManager::from_dir(&options.package_path)?
+            .with_keep_path(options.keep_path)
+            .with_strict(options.strict);
 
         Ok(Self {
             manifest,
@@ -121,6 +144,7 @@ impl Exporter {
             processed_crates: HashSet::new(),
             workspace_members: Vec::new(),
             patch: nexus_manager.root_manifest.patch().clone(),
+            output_manager: nexus_manager.clone().with_mode(options.mode),
             nexus_manager,
             crates_dir_name: options.crates_dir.clone(),
         })
@@ -170,6 +194,7 @@ impl Exporter {
 
         // Print summary
         self.print_summary();
+        self.nexus_manager.report_fallbacks();
 
         Ok(())
     }
@@ -209,7 +234,7 @@ impl Exporter {
         let target_dir = self.export_crates_dir.join(&package.name);
 
         // Create symbolic link for the package
-        self.create_symlink(&package.root_path, &target_dir)?;
+        self.link_package(&package.root_path, &target_dir)?;
 
         // Update tracking information
         self.processed_paths.insert(package.root_path.clone());
@@ -241,7 +266,17 @@ impl Exporter {
         let mut deps_to_process_recursively = Vec::new();
 
         // Process all dependencies with paths (original and newly resolved ones)
-        for (crate_name, dep) in &package_clone.dependencies {
+        let all_dependencies = DependencyKind::ALL.into_iter().flat_map(|kind| {
+            std::iter::once(package_clone.dependency_map(kind))
+                .chain(
+                    package_clone
+                        .target
+                        .values()
+                        .map(move |t| t.dependency_map(kind)),
+                )
+                .flat_map(|deps| deps.iter())
+        });
+        for (crate_name, dep) in all_dependencies {
             // Skip already processed crates
             if self.processed_crates.contains(crate_name) {
                 continue;
@@ -275,7 +310,7 @@ impl Exporter {
         // Convert to absolute path
         let dep = self
             .nexus_manager
-            .resolve_dependency(manifest_root_path, &crate_name, &dep)?;
+            .resolve_dependency(manifest_root_path, crate_name, dep)?;
         let Some(dep_path) = &dep.path else {
             warn!("No path found for dependency {}", crate_name);
             return Ok(None);
@@ -290,7 +325,7 @@ impl Exporter {
         let target_dir = self.export_crates_dir.join(crate_name);
 
         // Create symbolic link and update tracking
-        self.create_symlink(&canonical_path, &target_dir)?;
+        self.link_package(&canonical_path, &target_dir)?;
         self.workspace_members
             .push(format!("{}/{}", self.crates_dir_name, crate_name));
         info!(
@@ -378,7 +413,7 @@ impl Exporter {
                 );
 
                 // Process the patch as a path dependency
-                self.process_path_dependency(&self.root_path.clone(), crate_name, &patch_config)?;
+                self.process_path_dependency(&self.root_path.clone(), crate_name, patch_config)?;
                 self.patch.get_mut(registry_name).unwrap().insert(
                     crate_name.clone(),
                     DependencyModel {
@@ -401,7 +436,7 @@ impl Exporter {
 
     /// Create a workspace model for the export directory
     /// This method also prepares the models with correct dependency paths
-    fn create_export_workspace(&self) -> Result<WorkspaceModel> {
+    fn create_export_workspace(&mut self) -> Result<WorkspaceModel> {
         // Get source information from original manifest
         let (name, description, resolver) = match &self.manifest {
             ManifestModel::Workspace(ws) => {
@@ -419,6 +454,20 @@ impl Exporter {
             ),
         };
 
+        // Build profiles only take effect at the root of the exported workspace. They are
+        // those of the workspace of the source, which the generator merges over the nexus
+        // defaults exactly as `generate` does.
+        let profile = match &self.manifest {
+            ManifestModel::Workspace(ws) => ws.profile.clone(),
+            ManifestModel::Package(pkg) => self
+                .nexus_manager
+                .index()?
+                .find_workspace_containing(&pkg.root_path)
+                .map(|workspace| workspace.profile.clone())
+                .unwrap_or_default(),
+            ManifestModel::Nexus(_) => HashMap::new(),
+        };
+
         // Create workspace model directly with original dependencies if available
         let mut dependencies = HashMap::new();
         if let ManifestModel::Workspace(ws) = &self.manifest {
@@ -426,7 +475,7 @@ impl Exporter {
         }
 
         // Ensure all exported packages are defined in workspace dependencies
-        self.update_workspace_dependencies(&mut dependencies);
+        self.update_workspace_dependencies(&mut dependencies)?;
 
         // Create workspace model
         let workspace = WorkspaceModel {
@@ -434,7 +483,12 @@ impl Exporter {
             description,
             members: self.workspace_members.clone(),
             exclude: Vec::new(),
+            respect_gitignore: false,
             resolver,
+            root_package: false,
+            package: None,
+            lints: HashMap::new(),
+            profile,
             custom: HashMap::new(),
             dependencies,
             patch: self.patch.clone(),
@@ -446,7 +500,10 @@ impl Exporter {
     }
 
     /// Update workspace dependencies to include all exported packages
-    fn update_workspace_dependencies(&self, dependencies: &mut HashMap<String, DependencyModel>) {
+    fn update_workspace_dependencies(
+        &self,
+        dependencies: &mut HashMap<String, DependencyModel>,
+    ) -> Result<()> {
         // Ensure all exported packages are defined in workspace dependencies
         // This allows packages to reference each other through workspace dependencies
         for crate_name in &self.processed_crates {
@@ -477,12 +534,26 @@ impl Exporter {
                 debug!("Updated workspace dependency path for {}", dep_name);
             }
         }
+
+        // Outside of path mode, exported crates are depended on by version or through git
+        if self.output_manager.mode != ResolutionMode::Path {
+            for (dep_name, detailed) in dependencies.iter_mut() {
+                if !self.processed_crates.contains(dep_name) {
+                    continue;
+                }
+                let package = PackageModel::from_dir(&self.export_crates_dir.join(dep_name))?;
+                self.output_manager
+                    .apply_mode(dep_name, detailed, &package)?;
+            }
+        }
+
+        Ok(())
     }
 
     /// Generate Cargo.toml files using CargoGenerator
     fn generate_cargo_toml_files(&self, workspace: &WorkspaceModel) -> Result<()> {
         // Create the Cargo.toml generator
-        let generator = CargoGenerator::new(self.nexus_manager.clone());
+        let mut generator = CargoGenerator::new(self.nexus_manager.clone());
 
         // Generate workspace and package Cargo.toml files
         generator
@@ -496,6 +567,76 @@ impl Exporter {
     // Helper Methods
     // -------------------------------------------------------------------------
 
+    /// Link a package directory into the export directory
+    fn link_package(&mut self, source: &Path, target_dir: &Path) -> Result<()> {
+        match WorkspaceModel::from_dir(source) {
+            Ok(workspace) if workspace.root_package => {
+                let package = PackageModel::from_dir(source)?;
+                self.link_root_package(&package, target_dir)
+            }
+            _ => self.create_symlink(source, target_dir),
+        }
+    }
+
+    /// Link a root package, whose Cargo.toml also declares its workspace.
+    ///
+    /// Cargo rejects nested workspace roots, so the package gets a real directory with links
+    /// to its contents and a Cargo.toml of its own, pointing at the exported dependencies.
+    fn link_root_package(&mut self, package: &PackageModel, target_dir: &Path) -> Result<()> {
+        fs::create_dir_all(target_dir).context(format!(
+            "Failed to create directory {}",
+            target_dir.display()
+        ))?;
+        for entry in fs::read_dir(&package.root_path)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            if ROOT_PACKAGE_SKIPPED_FILES.contains(&file_name.to_string_lossy().as_ref()) {
+                continue;
+            }
+            self.create_symlink(&entry.path(), &target_dir.join(&file_name))?;
+        }
+
+        // Every local dependency is exported next to the package under its own name
+        let mut package = package.clone();
+        self.nexus_manager
+            .resolve_package_dependencies(&mut package)?;
+        let root_path = package.root_path.clone();
+        for kind in DependencyKind::ALL {
+            self.point_to_exported_crates(&root_path, package.dependency_map_mut(kind))?;
+            for target in package.target.values_mut() {
+                self.point_to_exported_crates(&root_path, target.dependency_map_mut(kind))?;
+            }
+        }
+
+        let mut generator =
+            CargoGenerator::new(self.nexus_manager.clone()).with_expand_workspace_fields(true);
+        generator
+            .render_standalone_package_cargo_toml(&mut package, &target_dir.join("Cargo.toml"))?
+            .write()
+    }
+
+    /// Point the local dependencies of a package at their exported copies, which sit next to
+    /// each other, or rewrite them according to the resolution mode
+    fn point_to_exported_crates(
+        &self,
+        root_path: &Path,
+        dependencies: &mut DependencyModelMap,
+    ) -> Result<()> {
+        for (crate_name, dep) in dependencies.iter_mut() {
+            let Some(path) = &dep.path else {
+                continue;
+            };
+            if self.output_manager.mode != ResolutionMode::Path {
+                let package = PackageModel::from_dir(&maybe_join(root_path, path))?;
+                self.output_manager.apply_mode(crate_name, dep, &package)?;
+            }
+            if dep.path.is_some() {
+                dep.path = Some(PathBuf::from("..").join(crate_name));
+            }
+        }
+        Ok(())
+    }
+
     /// Create a symbolic link with platform-specific implementation
     fn create_symlink(&self, source: &Path, target: &Path) -> Result<()> {
         #[cfg(unix)]
@@ -1,10 +1,11 @@
 //! Command implementation for generating Cargo.toml files from Magnet.toml
 
+use crate::MagnetError;
 use crate::generator::CargoGenerator;
-use crate::manager::ManifestManager;
+use crate::manager::{ManifestManager, ResolutionMode};
 use crate::models::WorkspaceModel;
 use crate::utils;
-use eyre::{Context, Result};
+use eyre::{Context, Result, bail};
 use std::path::PathBuf;
 use tracing::{debug, info};
 
@@ -20,6 +21,19 @@ pub struct GenerateOptions {
     pub include_cargo_dir: bool,
     /// Whether to create symlinks for .cargo directory (true) or copy it (false)
     pub symlink_cargo_dir: bool,
+    /// Edit existing Cargo.toml files in place, keeping comments and unmanaged sections
+    pub preserve_format: bool,
+    /// Write inherited `[workspace.package]` values into every package instead of keeping
+    /// `field.workspace = true`
+    pub expand_workspace_fields: bool,
+    /// Only compare the generated files with the ones on disk, without writing anything
+    pub check: bool,
+    /// How `nexus = true` dependencies are written
+    pub mode: ResolutionMode,
+    /// In publish mode, keep the `path` next to the `version`
+    pub keep_path: bool,
+    /// Fail on dependencies that cannot be resolved
+    pub strict: bool,
 }
 
 impl Default for GenerateOptions {
@@ -30,6 +44,12 @@ impl Default for GenerateOptions {
             copy_lock: true,
             include_cargo_dir: true,
             symlink_cargo_dir: true,
+            preserve_format: false,
+            expand_workspace_fields: false,
+            check: false,
+            mode: ResolutionMode::default(),
+            keep_path: false,
+            strict: false,
         }
     }
 }
@@ -40,18 +60,27 @@ pub fn generate(options: &GenerateOptions) -> Result<()> {
 
     info!("Processing: {}", config_path.canonicalize()?.display());
     // Process the root configuration file and recursively generate all nested workspaces
-    let nexus_manager = ManifestManager::from_dir(config_path)?;
+    let nexus_manager = ManifestManager::from_dir(config_path)?
+        .with_mode(options.mode)
+        .with_keep_path(options.keep_path)
+        .with_strict(options.strict);
 
     // Load the configuration
     let workspace = WorkspaceModel::from_dir(config_path)?;
 
+    if options.check {
+        return check_generated(nexus_manager, &workspace, options);
+    }
+
     // Clean directories if requested
     if options.clean {
         clean_workspace_directories(&workspace)?;
     }
 
     // Create a generator
-    let mut generator = CargoGenerator::new(nexus_manager.clone());
+    let mut generator = CargoGenerator::new(nexus_manager.clone())
+        .with_preserve_format(options.preserve_format)
+        .with_expand_workspace_fields(options.expand_workspace_fields);
 
     // Generate all Cargo.toml files for this workspace
     generator.generate_all(&workspace).context(format!(
@@ -69,11 +98,52 @@ pub fn generate(options: &GenerateOptions) -> Result<()> {
         handle_cargo_dir(&workspace, options.symlink_cargo_dir)?;
     }
 
+    nexus_manager.report_fallbacks();
     info!("Cargo.toml files updated successfully");
 
     Ok(())
 }
 
+/// Run the generator in memory and compare every Cargo.toml with the file on disk
+fn check_generated(
+    nexus_manager: ManifestManager,
+    workspace: &WorkspaceModel,
+    options: &GenerateOptions,
+) -> Result<()> {
+    let mut generator = CargoGenerator::new(nexus_manager)
+        .with_preserve_format(options.preserve_format)
+        .with_expand_workspace_fields(options.expand_workspace_fields);
+    let files = generator.render_all(workspace).context(format!(
+        "Failed to generate Cargo.toml files for {}",
+        options.config_path.display()
+    ))?;
+
+    let mut outdated = Vec::new();
+    for file in &files {
+        if let Some(diff) = file.diff()? {
+            print!("{}", diff);
+            outdated.push(file.path.clone());
+        }
+    }
+    if !outdated.is_empty() {
+        let paths: Vec<String> = outdated
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        bail!(MagnetError::OutOfDate {
+            message: format!(
+                "{} Cargo.toml file(s) are out of date, run `magnet generate` to update them:\n  {}",
+                outdated.len(),
+                paths.join("\n  ")
+            ),
+            files: outdated,
+        });
+    }
+
+    info!("All {} Cargo.toml files are up to date", files.len());
+    Ok(())
+}
+
 /// Clean directories in the workspace before generation
 fn clean_workspace_directories(workspace: &WorkspaceModel) -> Result<()> {
     info!("Cleaning workspace directories before generation");
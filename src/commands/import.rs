@@ -0,0 +1,288 @@
+//! Command implementation for importing existing Cargo workspaces into a nexus
+//!
+//! Every Cargo.toml below the imported directory gets a Magnet.toml next to it with the same
+//! content, except that `path` dependencies into other repositories become `nexus = true`.
+//! The directory itself gets a nexus Magnet.toml listing the workspaces and packages found.
+
+use crate::configs::ManifestConfig;
+use crate::document::{
+    DEPENDENCY_TABLES, MANAGED_KEYS, parse_document, strip_generated_header, table_mut,
+};
+use crate::utils::{diff_path, find_members};
+use eyre::{Context, Result, bail};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{Array, DocumentMut, Item, TableLike, value};
+use tracing::{info, warn};
+
+/// A Cargo workspace or package that is not part of a workspace; each one becomes a member
+/// of the nexus
+struct ImportUnit {
+    root_path: PathBuf,
+    /// Directories of the packages of the unit, including a root package
+    packages: Vec<PathBuf>,
+    /// Directories of the manifests to convert: the unit root and its member packages
+    manifests: Vec<PathBuf>,
+}
+
+/// Convert the Cargo workspaces and packages below `path` into a Magnet nexus
+pub fn import(path: &Path) -> Result<()> {
+    let root_path = path
+        .canonicalize()
+        .with_context(|| format!("Failed to access {}", path.display()))?;
+    if root_path.join("Magnet.toml").exists() {
+        bail!(
+            "{} already has a Magnet.toml, run `magnet generate` instead",
+            root_path.display()
+        );
+    }
+
+    let mut units = Vec::new();
+    let root_manifest = root_path.join("Cargo.toml");
+    if root_manifest.exists() {
+        let document = read_cargo_toml(&root_manifest)?;
+        if !document.contains_key("workspace") {
+            bail!(
+                "{} is a package, a nexus can only hold it through a workspace; import the directory holding it and its sibling repositories instead",
+                root_path.display()
+            );
+        }
+        units.push(import_unit(&root_path, &document)?);
+    }
+    for dir in find_members(&root_path, &["**".to_string()], &[], false)? {
+        // Directories with only a Magnet.toml are already managed by magnet
+        let manifest = dir.join("Cargo.toml");
+        if !manifest.exists()
+            || units
+                .iter()
+                .any(|unit| unit.manifests.contains(&dir) || unit.packages.contains(&dir))
+        {
+            continue;
+        }
+        let document = read_cargo_toml(&manifest)?;
+        units.push(import_unit(&dir, &document)?);
+    }
+    if units.is_empty() {
+        bail!(
+            "No Cargo workspace or package found below {}",
+            root_path.display()
+        );
+    }
+
+    let unit_of_package: HashMap<&Path, usize> = units
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, unit)| unit.packages.iter().map(move |dir| (dir.as_path(), idx)))
+        .collect();
+
+    let mut issues = Vec::new();
+    let mut root_document = None;
+    for (idx, unit) in units.iter().enumerate() {
+        for dir in &unit.manifests {
+            let magnet_toml = dir.join("Magnet.toml");
+            if magnet_toml.exists() {
+                issues.push(format!(
+                    "{} already exists and was left unchanged",
+                    magnet_toml.display()
+                ));
+                continue;
+            }
+            let manifest = dir.join("Cargo.toml");
+            let mut document = read_cargo_toml(&manifest)?;
+            let mut converter = Converter {
+                root_path: &root_path,
+                manifest_path: &manifest,
+                unit: idx,
+                unit_of_package: &unit_of_package,
+                issues: &mut issues,
+            };
+            converter.convert(&mut document);
+
+            if dir == &root_path {
+                root_document = Some(document);
+                continue;
+            }
+            write_manifest(&magnet_toml, &document)?;
+        }
+    }
+
+    // The nexus goes first in the root Magnet.toml, which may also hold a root workspace
+    let mut members = Array::new();
+    for unit in &units {
+        if unit.root_path != root_path {
+            let member = diff_path(&root_path, &unit.root_path);
+            members.push(member.to_string_lossy().replace('\\', "/"));
+        }
+    }
+    for member in members.iter_mut() {
+        member.decor_mut().set_prefix("\n    ");
+    }
+    members.set_trailing("\n");
+    members.set_trailing_comma(true);
+    let mut nexus = DocumentMut::new();
+    let table = table_mut(&mut nexus, "nexus")?;
+    if let Some(name) = root_path.file_name() {
+        table.insert("name", value(name.to_string_lossy().as_ref()));
+    }
+    table.insert("members", value(members));
+    let mut content = nexus.to_string();
+    if let Some(document) = root_document {
+        content.push('\n');
+        content.push_str(&document.to_string());
+    }
+    write_manifest(&root_path.join("Magnet.toml"), &parse_document(&content)?)?;
+
+    info!(
+        "Imported {} workspaces and packages into the nexus at {}",
+        units.len(),
+        root_path.display()
+    );
+    if !issues.is_empty() {
+        warn!(
+            "{} items could not be converted without losing information:",
+            issues.len()
+        );
+        for issue in &issues {
+            warn!("  {}", issue);
+        }
+    }
+
+    Ok(())
+}
+
+/// The workspace rooted at `root_path` with its members, or the package at `root_path`
+fn import_unit(root_path: &Path, document: &DocumentMut) -> Result<ImportUnit> {
+    let mut unit = ImportUnit {
+        root_path: root_path.to_path_buf(),
+        packages: Vec::new(),
+        manifests: vec![root_path.to_path_buf()],
+    };
+    if document.contains_key("package") {
+        unit.packages.push(root_path.to_path_buf());
+    }
+    if let Some(workspace) = document.get("workspace") {
+        let patterns = |key: &str| -> Vec<String> {
+            workspace
+                .get(key)
+                .and_then(Item::as_array)
+                .map(|patterns| {
+                    patterns
+                        .iter()
+                        .filter_map(|pattern| pattern.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        for member in find_members(root_path, &patterns("members"), &patterns("exclude"), false)? {
+            if member != root_path && member.join("Cargo.toml").exists() {
+                unit.packages.push(member.clone());
+                unit.manifests.push(member);
+            }
+        }
+    }
+    Ok(unit)
+}
+
+/// Rewrites the dependencies of one manifest, collecting what it could not convert
+struct Converter<'a> {
+    root_path: &'a Path,
+    manifest_path: &'a Path,
+    /// Index of the unit holding the manifest
+    unit: usize,
+    unit_of_package: &'a HashMap<&'a Path, usize>,
+    issues: &'a mut Vec<String>,
+}
+
+impl Converter<'_> {
+    fn convert(&mut self, document: &mut DocumentMut) {
+        for (key, _) in document.iter() {
+            if !MANAGED_KEYS.contains(&key) {
+                self.issues.push(format!(
+                    "[{}] of {} is not written by `magnet generate`; use `generate --preserve-format` to keep it in Cargo.toml",
+                    key,
+                    self.manifest_path.display()
+                ));
+            }
+        }
+
+        for table in DEPENDENCY_TABLES {
+            self.convert_table(document.get_mut(table), table);
+        }
+        if let Some(targets) = document.get_mut("target").and_then(Item::as_table_like_mut) {
+            for (target, item) in targets.iter_mut() {
+                for table in DEPENDENCY_TABLES {
+                    let table_name = format!("target.{}.{}", target.get(), table);
+                    self.convert_table(item.get_mut(table), &table_name);
+                }
+            }
+        }
+        if let Some(workspace) = document.get_mut("workspace") {
+            self.convert_table(workspace.get_mut("dependencies"), "workspace.dependencies");
+        }
+    }
+
+    /// Turn the `path` dependencies of `table` that point into another unit into
+    /// `nexus = true` dependencies
+    fn convert_table(&mut self, table: Option<&mut Item>, table_name: &str) {
+        let Some(table) = table.and_then(Item::as_table_like_mut) else {
+            return;
+        };
+        let manifest_dir = self.manifest_path.parent().unwrap_or(self.root_path);
+        for (name, item) in table.iter_mut() {
+            let Some(dep) = item.as_table_like_mut() else {
+                continue;
+            };
+            let Some(path) = dep.get("path").and_then(Item::as_str) else {
+                continue;
+            };
+            let Ok(target) = manifest_dir.join(path).canonicalize() else {
+                self.issues.push(format!(
+                    "'{}' in [{}] of {} points to {}, which does not exist; it was kept as a path",
+                    name.get(),
+                    table_name,
+                    self.manifest_path.display(),
+                    path
+                ));
+                continue;
+            };
+            match self.unit_of_package.get(target.as_path()) {
+                Some(&unit) if unit != self.unit => set_nexus(dep),
+                Some(_) => {}
+                None if !target.starts_with(self.root_path) => self.issues.push(format!(
+                    "'{}' in [{}] of {} points to {}, outside of the nexus; it was kept as a path",
+                    name.get(),
+                    table_name,
+                    self.manifest_path.display(),
+                    target.display()
+                )),
+                None => {}
+            }
+        }
+    }
+}
+
+/// Replace the `path` of a dependency by `nexus = true`, keeping its other keys
+fn set_nexus(dep: &mut dyn TableLike) {
+    dep.remove("path");
+    dep.insert("nexus", value(true));
+    dep.fmt();
+}
+
+/// Read a Cargo.toml, without the header of a file generated by magnet
+fn read_cargo_toml(path: &Path) -> Result<DocumentMut> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_document(strip_generated_header(&content))
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Write a converted manifest after checking that magnet can read it
+fn write_manifest(path: &Path, document: &DocumentMut) -> Result<()> {
+    let content = document.to_string();
+    ManifestConfig::from_toml_str(&content)
+        .with_context(|| format!("Converted manifest for {} is invalid", path.display()))?;
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    info!("Created {}", path.display());
+    Ok(())
+}
@@ -74,7 +74,7 @@ fn detect_config_type_from_path(path: &Path) -> MagnetConfigType {
 
     // Check if in a crates directory (indicates package)
     let is_in_crates_dir =
-        components.contains(&"crates") && components.last().map_or(false, |last| *last != "crates");
+        components.contains(&"crates") && components.last().is_some_and(|last| *last != "crates");
 
     // Check if in nexus directory (indicates nexus)
     let is_in_nexus_dir = components.contains(&"nexus");
@@ -1,19 +1,27 @@
 //! Command implementations for the magnet CLI
 
 // Child modules
+pub mod add;
 mod check;
 pub mod export; // Changed from mod to pub mod to expose ExportOptions
 pub mod generate; // Changed from mod to pub mod to expose GenerateOptions
+mod import;
 mod init;
+pub mod new;
+pub mod remove;
 mod submodule;
 mod tree;
 mod utils;
 
 // Re-export commands
+pub use add::add;
 pub use check::check;
 pub use export::export;
 pub use generate::generate;
+pub use import::import;
 pub use init::init;
+pub use new::new;
+pub use remove::remove;
 pub use submodule::{
     deinit as submodule_deinit, init as submodule_init, list as submodule_list,
     switch as submodule_switch, update as submodule_update,
@@ -0,0 +1,352 @@
+//! Command implementation for scaffolding new packages and workspaces
+
+use crate::document::{parse_document, table_mut};
+use crate::manager::ManifestManager;
+use crate::models::WorkspaceModel;
+use crate::utils::{diff_path, find_members};
+use eyre::{Context, ContextCompat, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, Value, value};
+use tracing::info;
+
+/// Package fields a new package takes from its workspace or the nexus
+const DEFAULT_FIELDS: &[&str] = &["edition", "license", "authors"];
+
+/// Edition of new packages when neither the workspace nor the nexus sets one
+const DEFAULT_EDITION: &str = "2024";
+
+/// Names Cargo refuses for packages: the built-in crates and the Rust keywords
+const RESERVED_NAMES: &[&str] = &[
+    "alloc",
+    "core",
+    "proc_macro",
+    "std",
+    "test",
+    "abstract",
+    "as",
+    "async",
+    "await",
+    "become",
+    "box",
+    "break",
+    "const",
+    "continue",
+    "crate",
+    "do",
+    "dyn",
+    "else",
+    "enum",
+    "extern",
+    "false",
+    "final",
+    "fn",
+    "for",
+    "gen",
+    "if",
+    "impl",
+    "in",
+    "let",
+    "loop",
+    "macro",
+    "match",
+    "mod",
+    "move",
+    "mut",
+    "override",
+    "priv",
+    "pub",
+    "ref",
+    "return",
+    "self",
+    "static",
+    "struct",
+    "super",
+    "trait",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "unsafe",
+    "unsized",
+    "use",
+    "virtual",
+    "where",
+    "while",
+    "yield",
+];
+
+/// Configuration options for the new command
+pub struct NewOptions {
+    /// Directory the command runs in; new packages are created below it
+    pub path: PathBuf,
+    /// Name of the package
+    pub name: String,
+    /// Create a library instead of a binary
+    pub lib: bool,
+    /// Workspace to create the package in, scaffolded as a member of the nexus if it does
+    /// not exist yet
+    pub workspace: Option<String>,
+}
+
+impl Default for NewOptions {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("."),
+            name: String::new(),
+            lib: false,
+            workspace: None,
+        }
+    }
+}
+
+/// Create a package with a Magnet.toml and its source files, and register it as a member
+/// of its workspace, or of the nexus when it is not part of a workspace
+pub fn new(options: &NewOptions) -> Result<()> {
+    let name = &options.name;
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!(
+            "Invalid package name '{}': use letters, digits, '-' and '_' only",
+            name
+        );
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        bail!(
+            "Invalid package name '{}': it cannot start with a digit",
+            name
+        );
+    }
+    if RESERVED_NAMES.contains(&name.replace('-', "_").as_str()) {
+        bail!("Invalid package name '{}': the name is reserved", name);
+    }
+
+    let mut manager = ManifestManager::from_dir(&options.path)?;
+    let (package_path, workspace) = match &options.workspace {
+        Some(workspace_name) => {
+            let workspace = match manager.get_workspace(workspace_name) {
+                Some(workspace) => workspace,
+                None => new_workspace(&mut manager, workspace_name)?,
+            };
+            (package_dir(&workspace).join(name), Some(workspace))
+        }
+        None => {
+            let package_path = options.path.canonicalize()?.join(name);
+            let workspace = manager
+                .index()?
+                .find_workspace_containing(&package_path)
+                .cloned();
+            (package_path, workspace)
+        }
+    };
+    if package_path.exists() {
+        bail!("{} already exists", package_path.display());
+    }
+
+    let mut document = DocumentMut::new();
+    let package = table_mut(&mut document, "package")?;
+    package.insert("name", value(name.as_str()));
+    package.insert("version", value("0.1.0"));
+    let workspace_package = workspace
+        .as_ref()
+        .and_then(|workspace| workspace.package.as_ref());
+    for field in DEFAULT_FIELDS {
+        let inherited = workspace_package.is_some_and(|package| match *field {
+            "edition" => package.edition.is_some(),
+            "license" => package.license.is_some(),
+            "authors" => package.authors.is_some(),
+            _ => package.custom.contains_key(*field),
+        });
+        if inherited {
+            let mut inherit = InlineTable::new();
+            inherit.insert("workspace", true.into());
+            package.insert(field, value(inherit));
+        } else if let Some(default) = nexus_default(&manager, field)? {
+            package.insert(field, value(default));
+        } else if *field == "edition" {
+            package.insert(field, value(DEFAULT_EDITION));
+        }
+    }
+    table_mut(&mut document, "dependencies")?;
+
+    let (source_file, source) = if options.lib {
+        ("lib.rs", format!("//! The {} library\n", name))
+    } else {
+        (
+            "main.rs",
+            "fn main() {\n    println!(\"Hello, world!\");\n}\n".to_string(),
+        )
+    };
+    fs::create_dir_all(package_path.join("src"))
+        .with_context(|| format!("Failed to create {}", package_path.display()))?;
+    fs::write(package_path.join("src").join(source_file), source)?;
+    fs::write(package_path.join("Magnet.toml"), document.to_string())?;
+    info!(
+        "Created {} package {} at {}",
+        if options.lib { "library" } else { "binary" },
+        name,
+        package_path.display()
+    );
+
+    match &workspace {
+        Some(workspace) => add_member(
+            &workspace.source_path,
+            &["workspace", "members"],
+            &workspace.root_path,
+            &workspace.members,
+            &workspace.exclude,
+            &package_path,
+        )?,
+        None => register_in_nexus(&manager, &package_path)?,
+    }
+
+    info!("Run `magnet generate` to create its Cargo.toml");
+    Ok(())
+}
+
+/// Scaffold a workspace named `name` at the root of the nexus, with `[workspace.package]`
+/// holding the nexus defaults, and add it to the nexus members
+fn new_workspace(manager: &mut ManifestManager, name: &str) -> Result<WorkspaceModel> {
+    let nexus = manager.nexus().with_context(|| {
+        format!(
+            "No workspace named '{}' found, and {} is not a nexus to create it in",
+            name,
+            manager.root_path.display()
+        )
+    })?;
+    let root_path = nexus.root_path.join(name);
+    if root_path.exists() {
+        bail!(
+            "{} already exists but is not a workspace of the nexus",
+            root_path.display()
+        );
+    }
+
+    let mut document = DocumentMut::new();
+    let workspace = table_mut(&mut document, "workspace")?;
+    workspace.insert("members", value(Array::from_iter(["crates/*"])));
+    workspace.insert("resolver", value("2"));
+    let mut package = Table::new();
+    for field in DEFAULT_FIELDS {
+        if let Some(default) = nexus_default(manager, field)? {
+            package.insert(field, value(default));
+        } else if *field == "edition" {
+            package.insert(field, value(DEFAULT_EDITION));
+        }
+    }
+    workspace.insert("package", Item::Table(package));
+    table_mut(&mut document, "workspace")?
+        .entry("dependencies")
+        .or_insert_with(|| Item::Table(Table::new()));
+
+    fs::create_dir_all(root_path.join("crates"))
+        .with_context(|| format!("Failed to create {}", root_path.display()))?;
+    fs::write(root_path.join("Magnet.toml"), document.to_string())?;
+    info!("Created workspace {} at {}", name, root_path.display());
+
+    register_in_nexus(manager, &root_path)?;
+    WorkspaceModel::from_dir(&root_path)
+}
+
+/// Directory new packages of a workspace go in: the directory of its first `dir/*` member
+/// pattern, or the workspace root
+fn package_dir(workspace: &WorkspaceModel) -> PathBuf {
+    workspace
+        .members
+        .iter()
+        .filter_map(|pattern| pattern.strip_suffix("/*"))
+        .find(|dir| !dir.contains(['*', '?', '[']))
+        .map_or_else(
+            || workspace.root_path.clone(),
+            |dir| workspace.root_path.join(dir),
+        )
+}
+
+/// Add `path` to the members of the nexus, if the manager is rooted at one
+fn register_in_nexus(manager: &ManifestManager, path: &Path) -> Result<()> {
+    let Some(nexus) = manager.nexus() else {
+        return Ok(());
+    };
+    add_member(
+        &nexus.root_path.join("Magnet.toml"),
+        &["nexus", "members"],
+        &nexus.root_path,
+        &nexus.members,
+        &nexus.exclude,
+        path,
+    )
+}
+
+/// Append `path` to the `members` array at `key_path` of the manifest at `manifest_path`,
+/// unless one of the existing `members` patterns already covers it
+fn add_member(
+    manifest_path: &Path,
+    key_path: &[&str],
+    root_path: &Path,
+    members: &[String],
+    exclude: &[String],
+    path: &Path,
+) -> Result<()> {
+    let path = path.canonicalize()?;
+    if find_members(root_path, members, exclude, false)?.contains(&path) {
+        return Ok(());
+    }
+    let member = diff_path(root_path, &path)
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    let content = fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+    let mut document = parse_document(&content)?;
+    let mut item = document.as_item_mut();
+    for key in key_path {
+        item = item
+            .as_table_like_mut()
+            .with_context(|| format!("`{}` is not a table", key))?
+            .entry(key)
+            .or_insert(Item::Value(Value::Array(Array::new())));
+    }
+    let array = item.as_array_mut().with_context(|| {
+        format!(
+            "`{}` of {} is not an array",
+            key_path.join("."),
+            manifest_path.display()
+        )
+    })?;
+    // On a multi-line array the new member goes on its own line like the last one
+    let prefix = array
+        .iter()
+        .last()
+        .and_then(|last| last.decor().prefix())
+        .and_then(|prefix| prefix.as_str())
+        .filter(|prefix| prefix.contains('\n'))
+        .map(str::to_string);
+    array.push(member.as_str());
+    if let (Some(prefix), Some(pushed)) = (prefix, array.iter_mut().last()) {
+        pushed.decor_mut().set_prefix(prefix);
+    }
+    fs::write(manifest_path, document.to_string())
+        .with_context(|| format!("Failed to write {}", manifest_path.display()))?;
+    info!(
+        "Added {} to the members of {}",
+        member,
+        manifest_path.display()
+    );
+    Ok(())
+}
+
+/// Value of a package field set in `[nexus]`, e.g. `edition = "2024"`
+fn nexus_default(manager: &ManifestManager, field: &str) -> Result<Option<Value>> {
+    let Some(default) = manager.nexus().and_then(|nexus| nexus.custom.get(field)) else {
+        return Ok(None);
+    };
+    let mut default = default
+        .to_string()
+        .parse::<Value>()
+        .with_context(|| format!("Invalid `{}` in [nexus]", field))?;
+    default.decor_mut().clear();
+    Ok(Some(default))
+}
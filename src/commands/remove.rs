@@ -0,0 +1,304 @@
+//! Command implementation for removing a dependency from a Magnet.toml file
+
+use crate::commands::utils::find_package;
+use crate::document::parse_document;
+use crate::manager::ManifestManager;
+use crate::models::{DependencyKind, DependencyModel, DependencySource, PackageModel};
+use eyre::{Context, ContextCompat, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item};
+use tracing::info;
+
+/// Configuration options for the remove command
+pub struct RemoveOptions {
+    /// Directory the command runs in; the package is looked up from here
+    pub path: PathBuf,
+    /// Name of the dependency
+    pub name: String,
+    /// Dependency table to remove the dependency from
+    pub kind: DependencyKind,
+    /// Package to remove the dependency from, instead of the package in `path`
+    pub package: Option<String>,
+    /// Also remove the shared definition the dependency inherited once no package uses it
+    pub prune: bool,
+}
+
+impl Default for RemoveOptions {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("."),
+            name: String::new(),
+            kind: DependencyKind::Normal,
+            package: None,
+            prune: false,
+        }
+    }
+}
+
+/// Remove a dependency from the Magnet.toml of a package, keeping its formatting.
+///
+/// When the dependency inherited a workspace- or nexus-level definition that no other
+/// package refers to any more, the definition is removed as well with `prune`, and
+/// reported otherwise.
+pub fn remove(options: &RemoveOptions) -> Result<()> {
+    let mut manager = ManifestManager::from_dir(&options.path)?;
+    let package = find_package(&mut manager, &options.path, options.package.as_deref())?;
+    let name = &options.name;
+    let table_name = options.kind.table_name();
+
+    let Some(dep) = package_dependencies(&package, options.kind)
+        .find(|(dep_name, _)| *dep_name == name)
+        .map(|(_, dep)| dep.clone())
+    else {
+        bail!(
+            "Package {} has no dependency '{}' in [{}]",
+            package.name,
+            name,
+            table_name
+        );
+    };
+    // Looked up before the entry is gone, since the lookup depends on it
+    let shared = if inherits_shared(&dep) {
+        manager.find_shared_dependency(&package.root_path, name, &dep)?
+    } else {
+        None
+    };
+
+    edit_document(&package.source_path, |document| {
+        let mut removed = document
+            .get_mut(table_name)
+            .and_then(Item::as_table_like_mut)
+            .and_then(|table| table.remove(name))
+            .is_some();
+        // Dependencies declared with `target = "..."` may live in a `[target.<cfg>]` table
+        if let Some(targets) = document.get_mut("target").and_then(Item::as_table_like_mut) {
+            for (_, target) in targets.iter_mut() {
+                removed |= target
+                    .get_mut(table_name)
+                    .and_then(Item::as_table_like_mut)
+                    .and_then(|table| table.remove(name))
+                    .is_some();
+            }
+        }
+        if removed && !has_feature_dependency(document, name) {
+            strip_feature_references(document, name);
+        }
+        Ok(removed)
+    })?;
+    info!(
+        "Removed {} from [{}] of {}",
+        name,
+        table_name,
+        package.source_path.display()
+    );
+
+    let Some((root_path, key, _)) = shared else {
+        return Ok(());
+    };
+    let edited = PackageModel::from_dir(&package.root_path)?;
+    if is_shared_dependency_used(&mut manager, &edited, &root_path, &key)? {
+        return Ok(());
+    }
+
+    // The nexus keeps shared dependencies in its top-level [dependencies], workspaces in
+    // [workspace.dependencies]
+    let is_nexus = manager
+        .nexus()
+        .is_some_and(|nexus| nexus.root_path == root_path);
+    let (source_path, table_path) = if is_nexus {
+        (root_path.join("Magnet.toml"), vec!["dependencies"])
+    } else {
+        let workspace = manager
+            .index()?
+            .workspaces()
+            .iter()
+            .find(|workspace| workspace.root_path == root_path)
+            .with_context(|| format!("No workspace found at {}", root_path.display()))?;
+        (
+            workspace.source_path.clone(),
+            vec!["workspace", "dependencies"],
+        )
+    };
+
+    // A workspace with only a Cargo.toml is never edited, like packages without a Magnet.toml
+    if source_path.file_name() != Some("Magnet.toml".as_ref()) {
+        info!(
+            "'{}' in [{}] of {} is no longer used by any package",
+            key,
+            table_path.join("."),
+            source_path.display()
+        );
+        return Ok(());
+    }
+    if !options.prune {
+        info!(
+            "'{}' in [{}] of {} is no longer used by any package, run with --prune to remove it",
+            key,
+            table_path.join("."),
+            source_path.display()
+        );
+        return Ok(());
+    }
+
+    edit_document(&source_path, |document| {
+        let mut item = document.as_item_mut();
+        for key in &table_path {
+            let Some(next) = item.get_mut(key) else {
+                return Ok(false);
+            };
+            item = next;
+        }
+        Ok(item
+            .as_table_like_mut()
+            .and_then(|table| table.remove(&key))
+            .is_some())
+    })?;
+    info!(
+        "Removed unused '{}' from [{}] of {}",
+        key,
+        table_path.join("."),
+        source_path.display()
+    );
+
+    Ok(())
+}
+
+/// Whether a dependency named `name` can still be enabled by features: one left in
+/// `[dependencies]` or `[build-dependencies]`, or in those tables of a `[target.<cfg>]`
+fn has_feature_dependency(document: &DocumentMut, name: &str) -> bool {
+    let tables = [
+        DependencyKind::Normal.table_name(),
+        DependencyKind::Build.table_name(),
+    ];
+    let has_dependency = |item: &Item| {
+        tables.iter().any(|table| {
+            item.get(table)
+                .and_then(Item::as_table_like)
+                .is_some_and(|table| table.contains_key(name))
+        })
+    };
+    has_dependency(document.as_item())
+        || document
+            .get("target")
+            .and_then(Item::as_table_like)
+            .is_some_and(|targets| targets.iter().any(|(_, target)| has_dependency(target)))
+}
+
+/// Remove the references to dependency `name` from `[features]`, like `cargo remove`:
+/// `dep:name`, `name/feature`, `name?/feature` and `name` itself unless a feature has that name
+fn strip_feature_references(document: &mut DocumentMut, name: &str) {
+    let Some(features) = document
+        .get_mut("features")
+        .and_then(Item::as_table_like_mut)
+    else {
+        return;
+    };
+    let is_feature = features.contains_key(name);
+    let dep = format!("dep:{}", name);
+    let prefixes = [format!("{}/", name), format!("{}?/", name)];
+    for (feature, item) in features.iter_mut() {
+        let Some(enabled) = item.as_array_mut() else {
+            continue;
+        };
+        let before = enabled.len();
+        enabled.retain(|value| {
+            let Some(value) = value.as_str() else {
+                return true;
+            };
+            !(value == dep
+                || prefixes
+                    .iter()
+                    .any(|prefix| value.starts_with(prefix.as_str()))
+                || (value == name && !is_feature))
+        });
+        if enabled.len() != before {
+            // The first value of a single-line array has no space after the `[`
+            if let Some(first) = enabled.get_mut(0)
+                && !first
+                    .decor()
+                    .prefix()
+                    .and_then(|prefix| prefix.as_str())
+                    .is_some_and(|prefix| prefix.contains('\n'))
+            {
+                first.decor_mut().set_prefix("");
+            }
+            info!(
+                "Removed the references to {} from feature '{}'",
+                name,
+                feature.get()
+            );
+        }
+    }
+}
+
+/// Dependencies of `kind` of a package, including the ones of its `[target.<cfg>]` tables
+fn package_dependencies(
+    package: &PackageModel,
+    kind: DependencyKind,
+) -> impl Iterator<Item = (&String, &DependencyModel)> {
+    package.dependency_map(kind).iter().chain(
+        package
+            .target
+            .values()
+            .flat_map(move |target| target.dependency_map(kind)),
+    )
+}
+
+/// Whether a dependency may take its definition from the workspace or the nexus
+fn inherits_shared(dep: &DependencyModel) -> bool {
+    match &dep.fallback {
+        Some(sources) => sources.contains(&DependencySource::Workspace),
+        None => dep.workspace(),
+    }
+}
+
+/// Whether any package still inherits the definition of `key` from the workspace or nexus
+/// at `root_path`; `edited` replaces the indexed copy of the package that was just changed
+fn is_shared_dependency_used(
+    manager: &mut ManifestManager,
+    edited: &PackageModel,
+    root_path: &Path,
+    key: &str,
+) -> Result<bool> {
+    let packages: Vec<PackageModel> = manager
+        .index()?
+        .packages()
+        .filter(|package| package.source_path != edited.source_path)
+        .cloned()
+        .chain([edited.clone()])
+        .collect();
+
+    for package in &packages {
+        for kind in DependencyKind::ALL {
+            for (name, dep) in package_dependencies(package, kind) {
+                if !inherits_shared(dep) {
+                    continue;
+                }
+                // A package whose lookup fails might still refer to the definition
+                let used = match manager.find_shared_dependency(&package.root_path, name, dep) {
+                    Ok(shared) => shared.is_some_and(|(shared_root, shared_key, _)| {
+                        shared_root == root_path && shared_key == key
+                    }),
+                    Err(_) => true,
+                };
+                if used {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Apply `edit` to the TOML file at `path` and write it back if it reports a change
+fn edit_document(path: &Path, edit: impl FnOnce(&mut DocumentMut) -> Result<bool>) -> Result<()> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut document = parse_document(&content)?;
+    if edit(&mut document)? {
+        fs::write(path, document.to_string())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
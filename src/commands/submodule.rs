@@ -1,6 +1,7 @@
 //! Command implementation for managing Git submodules
 
-use eyre::{Context, Result, eyre};
+use crate::MagnetError;
+use eyre::{Context, Result};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use tracing::{debug, error, info};
@@ -164,7 +165,7 @@ fn update_submodules(path: &Path, remote: bool) -> Result<()> {
 fn get_submodules(path: &Path) -> Result<Vec<PathBuf>> {
     let output = Command::new("git")
         .current_dir(path)
-        .args(&["config", "--file", ".gitmodules", "--get-regexp", "path"])
+        .args(["config", "--file", ".gitmodules", "--get-regexp", "path"])
         .output()
         .context("Failed to execute git config to get submodules")?;
 
@@ -174,7 +175,12 @@ fn get_submodules(path: &Path) -> Result<Vec<PathBuf>> {
             // No .gitmodules file, so no submodules
             return Ok(Vec::new());
         }
-        return Err(eyre!("Failed to get submodules: {}", error));
+        return Err(MagnetError::Git {
+            command: "config --file .gitmodules --get-regexp path".to_string(),
+            path: path.to_path_buf(),
+            message: error.trim().to_string(),
+        }
+        .into());
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -210,7 +216,12 @@ fn execute_git_command(path: &Path, args: &[&str]) -> Result<()> {
         ))?;
 
     if !status.success() {
-        return Err(eyre!("Git command failed with exit code: {}", status));
+        return Err(MagnetError::Git {
+            command: args.join(" "),
+            path: path.to_path_buf(),
+            message: format!("exited with {}", status),
+        }
+        .into());
     }
 
     Ok(())
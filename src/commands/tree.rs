@@ -1,6 +1,8 @@
 //! Command implementation for displaying workspace hierarchy as a tree
 
-use crate::models::{ManifestModel, NexusModel, PackageModel, WorkspaceModel};
+use crate::models::{
+    DependencyKind, ManifestModel, NexusMembers, NexusModel, PackageModel, WorkspaceModel,
+};
 use eyre::Result;
 use std::path::Path;
 use tracing::info;
@@ -30,6 +32,16 @@ fn print_manifest_tree(
     }
 }
 fn print_nexus_tree(nexus: &NexusModel, depth: u32, prefix: &str) -> Result<()> {
+    let members = nexus.members()?;
+    print_nexus_members(nexus, &members, depth, prefix)
+}
+
+fn print_nexus_members(
+    nexus: &NexusModel,
+    members: &NexusMembers,
+    depth: u32,
+    prefix: &str,
+) -> Result<()> {
     // Print Nexus details
     info!(
         "{}{} 🧲 Nexus: {} ({})",
@@ -39,10 +51,21 @@ fn print_nexus_tree(nexus: &NexusModel, depth: u32, prefix: &str) -> Result<()>
         nexus.root_path.display()
     );
 
+    // Print nested nexuses with their own members
+    let has_more = !members.workspaces.is_empty() || !members.packages.is_empty();
+    for (idx, (nested, nested_members)) in members.nexuses.iter().enumerate() {
+        let is_last_nexus = idx == members.nexuses.len() - 1 && !has_more;
+        let nexus_prefix = if is_last_nexus {
+            "└── "
+        } else {
+            "├── "
+        };
+        print_nexus_members(nested, nested_members, depth + 1, nexus_prefix)?;
+    }
     // Print workspace tree
-    let workspaces = nexus.list_workspaces()?;
+    let workspaces = &members.workspaces;
     for (idx, workspace) in workspaces.iter().enumerate() {
-        let is_last_workspace = idx == workspaces.len() - 1;
+        let is_last_workspace = idx == workspaces.len() - 1 && members.packages.is_empty();
         let workspace_prefix = if is_last_workspace {
             "└── "
         } else {
@@ -51,7 +74,8 @@ fn print_nexus_tree(nexus: &NexusModel, depth: u32, prefix: &str) -> Result<()>
         print_workspace_tree(workspace, depth + 1, workspace_prefix, is_last_workspace)?;
     }
     // Print packages
-    let packages = nexus.list_packages()?;
+    let packages = &members.packages;
+    let package_indent = "  ".repeat(depth as usize + 1);
     for (idx, package) in packages.iter().enumerate() {
         let is_last_package = idx == packages.len() - 1;
         let package_prefix = if is_last_package {
@@ -59,7 +83,7 @@ fn print_nexus_tree(nexus: &NexusModel, depth: u32, prefix: &str) -> Result<()>
         } else {
             "├── "
         };
-        print_package_tree(package, prefix, package_prefix, is_last_package)?;
+        print_package_tree(package, &package_indent, package_prefix, is_last_package)?;
     }
     Ok(())
 }
@@ -94,7 +118,7 @@ fn print_workspace_tree(
         // Use the correct indentation for packages
         let package_indent = format!("{}{}", "  ".repeat(depth as usize), indent);
         print_package_tree(
-            &package,
+            package,
             package_indent.as_str(),
             package_prefix,
             is_last_package,
@@ -122,18 +146,38 @@ fn print_package_tree(
     // Prepare the indent for the package children
     let next_indent = format!("{}{}", parent_indent, if is_last { "    " } else { "│   " });
 
-    // Dependencies
-    if !package.dependencies.is_empty() {
-        for (idx, (crate_, dep)) in package.dependencies.iter().enumerate() {
-            let is_last_dep = idx == package.dependencies.len() - 1;
-            let dep_prefix = if is_last_dep {
-                "└── "
-            } else {
-                "├── "
+    // Dependencies of every kind, normal dependencies first, then target-specific ones
+    let mut dependencies = Vec::new();
+    for kind in DependencyKind::ALL {
+        for (crate_, dep) in package.dependency_map(kind) {
+            let label = match kind {
+                DependencyKind::Normal => String::new(),
+                _ => format!(" [{}]", kind),
             };
-            info!("{}{} 📄{} = {}", next_indent, dep_prefix, crate_, dep);
+            dependencies.push((crate_, dep, label));
+        }
+    }
+    for (target, target_deps) in &package.target {
+        for kind in DependencyKind::ALL {
+            for (crate_, dep) in target_deps.dependency_map(kind) {
+                dependencies.push((crate_, dep, format!(" [target.{}.{}]", target, kind)));
+            }
         }
     }
+    for (idx, (crate_, dep, label)) in dependencies.iter().enumerate() {
+        let is_last_dep = idx == dependencies.len() - 1;
+        let dep_prefix = if is_last_dep {
+            "└── "
+        } else {
+            "├── "
+        };
+        // Renamed dependencies show the alias and the real package name
+        let name = match &dep.package {
+            Some(package) if package != *crate_ => format!("{} → {}", crate_, package),
+            _ => crate_.to_string(),
+        };
+        info!("{}{} 📄{} = {}{}", next_indent, dep_prefix, name, dep, label);
+    }
 
     Ok(())
 }
@@ -1,2 +1,69 @@
 //! Utility functions shared across different commands
 
+use crate::MagnetError;
+use crate::manager::ManifestManager;
+use crate::models::PackageModel;
+use eyre::{Context, Result, bail};
+use std::path::Path;
+
+/// The package named `name`, or the package whose directory contains `path`, to edit the
+/// Magnet.toml of.
+///
+/// Packages with only a Cargo.toml are refused: magnet does not write Cargo.toml by hand, and
+/// Magnet-only keys such as `nexus = true` would make it invalid for Cargo.
+pub fn find_package(
+    manager: &mut ManifestManager,
+    path: &Path,
+    name: Option<&str>,
+) -> Result<PackageModel> {
+    let package = locate_package(manager, path, name)?;
+    if package.source_path.file_name() != Some("Magnet.toml".as_ref()) {
+        bail!(
+            "Package {} at {} has no Magnet.toml, run `magnet import` to create one",
+            package.name,
+            package.root_path.display()
+        );
+    }
+    Ok(package)
+}
+
+fn locate_package(
+    manager: &mut ManifestManager,
+    path: &Path,
+    name: Option<&str>,
+) -> Result<PackageModel> {
+    if let Some(name) = name {
+        let packages = manager.index()?.find_packages(name);
+        return match packages {
+            [package] => Ok(package.clone()),
+            [] => bail!("No package named '{}' found", name),
+            _ => {
+                let paths: Vec<String> = packages
+                    .iter()
+                    .map(|package| format!("  - {}", package.root_path.display()))
+                    .collect();
+                bail!(
+                    "Several packages are named '{}', run the command from the directory of the one to change:\n{}",
+                    name,
+                    paths.join("\n")
+                )
+            }
+        };
+    }
+
+    // The closest manifest decides, so running from a workspace root does not pick a
+    // package further up
+    let path = path.canonicalize()?;
+    for dir in path.ancestors() {
+        if !dir.join("Magnet.toml").exists() && !dir.join("Cargo.toml").exists() {
+            continue;
+        }
+        return PackageModel::from_dir(dir).with_context(|| {
+            format!(
+                "No package at {}, pass the package to change with -p",
+                dir.display()
+            )
+        });
+    }
+    bail!(MagnetError::ManifestNotFound { path })
+}
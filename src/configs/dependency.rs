@@ -1,5 +1,6 @@
 //! Dependency configuration for Magnet.toml files
 
+use crate::models::{DependencySource, NexusDependency};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Display;
@@ -19,7 +20,13 @@ pub struct DetailedDependencyConfig {
     pub path: Option<PathBuf>,
     /// Automatically resolve path to this dependency if found in any workspace
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub nexus: Option<bool>,
+    pub nexus: Option<NexusDependency>,
+    /// Whether the package found in the nexus must satisfy `version` (defaults to true)
+    #[serde(rename = "version-check", skip_serializing_if = "Option::is_none")]
+    pub version_check: Option<bool>,
+    /// Sources tried in order to resolve the dependency, the first one that applies is used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<Vec<DependencySource>>,
     /// Git repository URL
     #[serde(skip_serializing_if = "Option::is_none")]
     pub git: Option<String>,
@@ -61,6 +68,7 @@ pub struct DetailedDependencyConfig {
 /// Configuration for a single dependency
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum DependencyConfig {
     /// Simple version string: e.g., "1.0.0"
     Simple(String),
@@ -78,6 +86,13 @@ impl Display for DetailedDependencyConfig {
         if let Some(nexus) = &self.nexus {
             write!(f, "nexus = {}, ", nexus)?;
         }
+        if let Some(version_check) = &self.version_check {
+            write!(f, "version-check = {}, ", version_check)?;
+        }
+        if let Some(fallback) = &self.fallback {
+            let fallback: Vec<String> = fallback.iter().map(ToString::to_string).collect();
+            write!(f, "fallback = {:?}, ", fallback)?;
+        }
         if let Some(git) = &self.git {
             write!(f, "git = {:?}, ", git)?;
         }
@@ -149,6 +164,8 @@ impl From<DetailedDependencyConfig> for DependencyModel {
             version: config.version,
             path: config.path,
             nexus: config.nexus,
+            version_check: config.version_check,
+            fallback: config.fallback,
             git: config.git,
             branch: config.branch,
             tag: config.tag,
@@ -172,6 +189,8 @@ impl From<DependencyModel> for DetailedDependencyConfig {
             version: model.version,
             path: model.path,
             nexus: model.nexus,
+            version_check: model.version_check,
+            fallback: model.fallback,
             git: model.git,
             branch: model.branch,
             tag: model.tag,
@@ -204,4 +223,4 @@ impl From<DependencyConfig> for DependencyModel {
             DependencyConfig::Detailed(detailed) => detailed.into(),
         }
     }
-}
\ No newline at end of file
+}
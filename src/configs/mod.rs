@@ -6,21 +6,24 @@
 mod dependency;
 mod nexus;
 mod package;
+mod target;
 mod workspace;
 
 pub use dependency::*;
 pub use nexus::*;
 pub use package::*;
+pub use target::*;
 pub use workspace::*;
 
-use eyre::{Context, Result};
+use crate::MagnetError;
+use crate::models::{BuildTargetModel, FeatureMap, LintsMap, PatchMap, ProfileMap};
+use eyre::{Context, Result, bail};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use crate::models::PatchMap;
 
 /// Type of Magnet.toml configuration file
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum MagnetConfigType {
     /// Nexus configuration (top-level, manages multiple workspaces)
@@ -28,15 +31,10 @@ pub enum MagnetConfigType {
     /// Workspace configuration (manages multiple packages)
     Workspace,
     /// Package configuration (individual package)
+    #[default]
     Package,
 }
 
-impl Default for MagnetConfigType {
-    fn default() -> Self {
-        Self::Package
-    }
-}
-
 /// The main configuration structure representing a Magnet.toml file
 /// which is a superset of Cargo.toml
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -62,6 +60,33 @@ pub struct ManifestConfig {
     /// Build dependencies shared across workspace members
     #[serde(default, skip_serializing_if = "DependencyConfigMap::is_empty")]
     pub build_dependencies: DependencyConfigMap,
+    /// Target-specific dependency tables, e.g. `[target.'cfg(unix)'.dependencies]`
+    #[serde(default, skip_serializing_if = "TargetDependencyConfigMap::is_empty")]
+    pub target: TargetDependencyConfigMap,
+    /// Features of the package
+    #[serde(default, skip_serializing_if = "FeatureMap::is_empty")]
+    pub features: FeatureMap,
+    /// Library target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lib: Option<BuildTargetModel>,
+    /// Binary targets
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bin: Vec<BuildTargetModel>,
+    /// Integration test targets
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub test: Vec<BuildTargetModel>,
+    /// Benchmark targets
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bench: Vec<BuildTargetModel>,
+    /// Example targets
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub example: Vec<BuildTargetModel>,
+    /// Lints of the package, or `workspace = true` to use `[workspace.lints]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lints: Option<InheritableField<LintsMap>>,
+    /// Build profiles; only honored in nexus and workspace manifests
+    #[serde(default, skip_serializing_if = "ProfileMap::is_empty")]
+    pub profile: ProfileMap,
     /// Patch section for overriding dependencies
     #[serde(default, skip_serializing_if = "PatchMap::is_empty")]
     pub patch: PatchMap,
@@ -87,19 +112,27 @@ impl ManifestConfig {
 
     /// Actually load the file from disk (no caching)
     fn load_file(path: &Path) -> Result<Self> {
-        let path = path.canonicalize().with_context(|| {
-            format!(
-                "Failed to canonicalize path for Magnet.toml: {}",
-                path.display()
-            )
-        })?;
+        let path = match path.canonicalize() {
+            Ok(path) => path,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                bail!(MagnetError::ManifestNotFound {
+                    path: path.to_path_buf(),
+                })
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to access {}", path.display()));
+            }
+        };
         // Read the file content
         let content = std::fs::read_to_string(&path)
             .with_context(|| format!("Failed to read Magnet.toml from {}", path.display()))?;
 
         // Parse the TOML
-        let mut config: Self = toml::from_str(&content)
-            .with_context(|| format!("Failed to parse Magnet.toml from {}", path.display()))?;
+        let mut config: Self =
+            toml::from_str(&content).map_err(|err| MagnetError::InvalidManifest {
+                path: path.clone(),
+                message: err.to_string(),
+            })?;
 
         // Store the source path
         config.source_path = Some(path);
@@ -174,6 +207,15 @@ impl ManifestConfig {
             dependencies: HashMap::new(),
             dev_dependencies: HashMap::new(),
             build_dependencies: HashMap::new(),
+            target: HashMap::new(),
+            features: FeatureMap::new(),
+            lib: None,
+            bin: Vec::new(),
+            test: Vec::new(),
+            bench: Vec::new(),
+            example: Vec::new(),
+            lints: None,
+            profile: ProfileMap::new(),
             patch: PatchMap::new(),
             source_path: None,
             config_type: MagnetConfigType::default(),
@@ -191,19 +233,17 @@ impl ManifestConfig {
 
     /// Get the package version
     pub fn get_version(&self) -> Option<String> {
-        let version = self.package.as_ref()?.version.clone();
-        Some(version)
+        self.package.as_ref()?.version.value().cloned()
     }
 
     /// Get the package/project edition
     pub fn get_edition(&self) -> Option<String> {
-        self.package.as_ref()?.edition.clone()
+        self.package.as_ref()?.edition.as_ref()?.value().cloned()
     }
 
     /// Get the package/project description
     pub fn get_description(&self) -> Option<String> {
-        let desc = self.package.as_ref()?.description.clone();
-        Some(desc)
+        self.package.as_ref()?.description.value().cloned()
     }
 
     /// Get a formatted name for a node in the tree display based on config
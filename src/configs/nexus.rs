@@ -1,22 +1,35 @@
 //! Nexus configuration for Magnet.toml files
 
+use crate::models::LintsMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Nexus-specific configuration
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct NexusConfig {
     /// Name of the nexus
     pub name: Option<String>,
     /// Version of the nexus
     pub version: Option<String>,
     pub members: Vec<String>,
+    #[serde(default)]
     pub exclude: Vec<String>,
+    /// Member workspaces preferred, in order, when several of them hold a package with the
+    /// name of a `nexus = true` dependency
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub priority: Vec<String>,
+    /// Skip members ignored by git
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub respect_gitignore: bool,
     /// Description of the nexus
     #[serde(default)]
     pub description: Option<String>,
+    /// Default lints merged into the `[workspace.lints]` of every workspace in the nexus
+    #[serde(default, skip_serializing_if = "LintsMap::is_empty")]
+    pub lints: LintsMap,
 
     /// Custom nexus metadata
     #[serde(flatten)]
     pub custom: HashMap<String, toml::Value>,
-}
\ No newline at end of file
+}
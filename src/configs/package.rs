@@ -1,39 +1,128 @@
 //! Package configuration for Magnet.toml files
 
+use crate::configs::DependencyConfigMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use crate::configs::DependencyConfigMap;
 
 /// Package-specific configuration
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct PackageConfig {
     /// Name of the package
     pub name: String,
     /// Version of the package
-    pub version: String,
+    pub version: InheritableField<String>,
     /// Description of the package
     #[serde(default)]
-    pub description: String,
-    pub edition: Option<String>,
+    pub description: InheritableField<String>,
+    pub edition: Option<InheritableField<String>>,
     /// Authors of the package
     #[serde(default)]
-    pub authors: Vec<String>,
+    pub authors: InheritableField<Vec<String>>,
     /// Package homepage
     #[serde(default)]
-    pub homepage: Option<String>,
+    pub homepage: Option<InheritableField<String>>,
     /// Package repository
     #[serde(default)]
-    pub repository: Option<String>,
+    pub repository: Option<InheritableField<String>>,
     /// Package documentation URL
     #[serde(default)]
-    pub documentation: Option<String>,
+    pub documentation: Option<InheritableField<String>>,
     /// Package license
     #[serde(default)]
-    pub license: Option<String>,
+    pub license: Option<InheritableField<String>>,
+    /// Path to a custom license file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license_file: Option<InheritableField<String>>,
+    /// Minimum supported Rust version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rust_version: Option<InheritableField<String>>,
+    /// Keywords for the registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<InheritableField<Vec<String>>>,
+    /// Categories for the registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub categories: Option<InheritableField<Vec<String>>>,
+    /// Whether and where the package may be published
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish: Option<InheritableField<PublishConfig>>,
     /// Custom package metadata
     #[serde(flatten)]
     pub custom: HashMap<String, toml::Value>,
 }
+
+/// A package field that is either set directly or inherited from `[workspace.package]`,
+/// e.g. `version.workspace = true`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum InheritableField<T> {
+    /// Value set in the package itself
+    Value(T),
+    /// Value inherited from the workspace
+    Inherited(WorkspaceInherit),
+}
+
+/// Marker table of an inherited field: `{ workspace = true }`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WorkspaceInherit {
+    pub workspace: bool,
+}
+
+impl<T> InheritableField<T> {
+    /// A field inherited from `[workspace.package]`
+    pub fn inherited() -> Self {
+        InheritableField::Inherited(WorkspaceInherit { workspace: true })
+    }
+
+    pub fn is_inherited(&self) -> bool {
+        matches!(self, InheritableField::Inherited(_))
+    }
+
+    /// The value set in the package, if it is not inherited
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            InheritableField::Value(value) => Some(value),
+            InheritableField::Inherited(_) => None,
+        }
+    }
+}
+
+impl<T: Default> Default for InheritableField<T> {
+    fn default() -> Self {
+        InheritableField::Value(T::default())
+    }
+}
+
+impl From<&str> for InheritableField<String> {
+    fn from(value: &str) -> Self {
+        InheritableField::Value(value.to_string())
+    }
+}
+
+impl From<String> for InheritableField<String> {
+    fn from(value: String) -> Self {
+        InheritableField::Value(value)
+    }
+}
+
+/// The `publish` field: `false` to forbid publishing, or the registries that are allowed
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PublishConfig {
+    Enabled(bool),
+    Registries(Vec<String>),
+}
+
+impl PublishConfig {
+    /// Whether the package may be published to any registry
+    pub fn is_publishable(&self) -> bool {
+        match self {
+            PublishConfig::Enabled(enabled) => *enabled,
+            PublishConfig::Registries(registries) => !registries.is_empty(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct CargoPackageConfig {
     /// Name of the package
@@ -66,4 +155,4 @@ pub struct CargoPackageConfigWrapper {
     pub dependencies: DependencyConfigMap,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub patch: Option<toml::value::Table>,
-}
\ No newline at end of file
+}
@@ -0,0 +1,70 @@
+//! Target-specific dependency configuration for Magnet.toml files
+
+use crate::configs::DependencyConfigMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Map of target (a triple or `cfg(...)` expression) to its dependency tables
+pub type TargetDependencyConfigMap = HashMap<String, TargetDependencyConfig>;
+
+/// Dependency tables of a `[target.'cfg(...)']` section
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TargetDependencyConfig {
+    /// Dependencies only used on this target
+    #[serde(default, skip_serializing_if = "DependencyConfigMap::is_empty")]
+    pub dependencies: DependencyConfigMap,
+    /// Development dependencies only used on this target
+    #[serde(default, skip_serializing_if = "DependencyConfigMap::is_empty")]
+    pub dev_dependencies: DependencyConfigMap,
+    /// Build dependencies only used on this target
+    #[serde(default, skip_serializing_if = "DependencyConfigMap::is_empty")]
+    pub build_dependencies: DependencyConfigMap,
+}
+
+// Implement conversion between models and configs
+use crate::models::TargetDependencyModel;
+
+impl From<TargetDependencyConfig> for TargetDependencyModel {
+    fn from(config: TargetDependencyConfig) -> Self {
+        TargetDependencyModel {
+            dependencies: config
+                .dependencies
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+            dev_dependencies: config
+                .dev_dependencies
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+            build_dependencies: config
+                .build_dependencies
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+        }
+    }
+}
+
+impl From<TargetDependencyModel> for TargetDependencyConfig {
+    fn from(model: TargetDependencyModel) -> Self {
+        TargetDependencyConfig {
+            dependencies: model
+                .dependencies
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+            dev_dependencies: model
+                .dev_dependencies
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+            build_dependencies: model
+                .build_dependencies
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+        }
+    }
+}
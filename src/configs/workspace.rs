@@ -1,11 +1,12 @@
 //! Workspace configuration for Magnet.toml files
 
 use crate::configs::DependencyConfigMap;
+use crate::models::{LintsMap, WorkspacePackageModel};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Workspace configuration (legacy)
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct WorkspaceConfig {
     /// Workspace members (glob patterns)
@@ -14,28 +15,25 @@ pub struct WorkspaceConfig {
     /// Excluded workspace members (glob patterns)
     #[serde(default)]
     pub exclude: Vec<String>,
+    /// Skip members ignored by git; Magnet-only, never written to Cargo.toml
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub respect_gitignore: bool,
     /// Cargo resolver version (1 or 2)
     #[serde(default)]
     pub resolver: Option<String>,
+    /// Package fields inherited by members
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<WorkspacePackageModel>,
     #[serde(default, skip_serializing_if = "DependencyConfigMap::is_empty")]
     pub dependencies: DependencyConfigMap,
+    /// Lints shared by members with `lints.workspace = true`
+    #[serde(default, skip_serializing_if = "LintsMap::is_empty")]
+    pub lints: LintsMap,
     /// Custom workspace metadata
     #[serde(flatten)]
     pub custom: HashMap<String, toml::Value>,
 }
 
-impl Default for WorkspaceConfig {
-    fn default() -> Self {
-        Self {
-            members: Vec::new(),
-            exclude: Vec::new(),
-            resolver: None,
-            dependencies: DependencyConfigMap::new(),
-            custom: HashMap::new(),
-        }
-    }
-}
-
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CargoWorkspaceConfig {
     /// Workspace members (glob patterns)
@@ -47,10 +45,9 @@ pub struct CargoWorkspaceConfig {
     /// Cargo resolver version (1 or 2)
     #[serde(default)]
     pub resolver: Option<String>,
-    
+
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub dependencies: DependencyConfigMap,
-    
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -58,4 +55,4 @@ pub struct CargoWorkspaceConfigWrapper {
     pub workspace: CargoWorkspaceConfig,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub patch: Option<toml::value::Table>,
-}
\ No newline at end of file
+}
@@ -0,0 +1,389 @@
+//! Format-preserving editing of TOML documents
+//!
+//! Generated Cargo.toml files are merged into the existing document instead of
+//! overwriting it, so comments, key order and sections Magnet does not model stay
+//! byte-identical.
+
+use eyre::{Context, ContextCompat, Result};
+use serde::Serialize;
+use std::path::Path;
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value};
+
+/// Top-level keys of a Cargo.toml file that are owned by Magnet
+pub const MANAGED_KEYS: &[&str] = &[
+    "workspace",
+    "package",
+    "dependencies",
+    "dev-dependencies",
+    "build-dependencies",
+    "target",
+    "features",
+    "lib",
+    "bin",
+    "test",
+    "bench",
+    "example",
+    "lints",
+    "profile",
+    "patch",
+];
+
+/// First line of the header written at the top of every generated Cargo.toml
+pub const GENERATED_HEADER_PREFIX: &str = "# This file is generated by magnet";
+
+/// Header marking a file as generated from `source` by this version of magnet
+pub fn generated_header(source: &Path) -> String {
+    format!(
+        "{} {} from {}.\n# Do not edit it by hand: edit {} and run `magnet generate` instead.\n",
+        GENERATED_HEADER_PREFIX,
+        crate::VERSION,
+        source.display(),
+        source.display()
+    )
+}
+
+/// Whether a file starts with the magnet generated-file header
+pub fn is_generated(content: &str) -> bool {
+    content.starts_with(GENERATED_HEADER_PREFIX)
+}
+
+/// Remove the generated-file header (and the blank line after it) from a file
+pub fn strip_generated_header(content: &str) -> &str {
+    if !is_generated(content) {
+        return content;
+    }
+    let mut rest = content;
+    for _ in 0..2 {
+        if !rest.starts_with('#') {
+            break;
+        }
+        rest = rest.split_once('\n').map_or("", |(_, rest)| rest);
+    }
+    rest.strip_prefix('\n').unwrap_or(rest)
+}
+
+/// Names of the dependency tables inside a manifest
+pub const DEPENDENCY_TABLES: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Parse a TOML document, keeping its formatting
+pub fn parse_document(content: &str) -> Result<DocumentMut> {
+    content
+        .parse::<DocumentMut>()
+        .context("Failed to parse TOML document")
+}
+
+/// Serialize a manifest into a TOML document in the layout Cargo uses:
+/// one inline table per dependency, with dependency and feature tables sorted by key
+pub fn manifest_document<T: Serialize>(manifest: &T) -> Result<DocumentMut> {
+    let toml_string =
+        toml::to_string_pretty(manifest).context("Failed to convert manifest to TOML")?;
+    let mut document = parse_document(&toml_string)?;
+
+    let root = document.as_table_mut();
+    normalize_dependency_tables(root);
+    normalize_lints(root);
+    if let Some(workspace) = root.get_mut("workspace").and_then(Item::as_table_mut) {
+        normalize_dependency_tables(workspace);
+        normalize_lints(workspace);
+    }
+    if let Some(targets) = root.get_mut("target").and_then(Item::as_table_mut) {
+        for (_, target) in targets.iter_mut() {
+            if let Some(target) = target.as_table_mut() {
+                normalize_dependency_tables(target);
+            }
+        }
+        sort_tables(targets);
+    }
+    if let Some(patches) = root.get_mut("patch").and_then(Item::as_table_mut) {
+        for (_, registry) in patches.iter_mut() {
+            if let Some(registry) = registry.as_table_mut() {
+                inline_entries(registry);
+            }
+        }
+    }
+    if let Some(profiles) = root.get_mut("profile").and_then(Item::as_table_mut) {
+        sort_tables(profiles);
+    }
+    if let Some(features) = root.get_mut("features").and_then(Item::as_table_mut) {
+        features.sort_values();
+    }
+
+    Ok(document)
+}
+
+fn normalize_dependency_tables(table: &mut Table) {
+    for name in DEPENDENCY_TABLES {
+        if let Some(dependencies) = table.get_mut(name).and_then(Item::as_table_mut) {
+            inline_entries(dependencies);
+        }
+    }
+}
+
+fn normalize_lints(table: &mut Table) {
+    if let Some(lints) = table.get_mut("lints").and_then(Item::as_table_mut) {
+        for (_, tool) in lints.iter_mut() {
+            if let Some(tool) = tool.as_table_mut() {
+                inline_entries(tool);
+            }
+        }
+        sort_tables(lints);
+    }
+}
+
+/// Sort `table` and its sub-tables by key, so maps serialize in a stable order
+fn sort_tables(table: &mut Table) {
+    let mut positions = Vec::new();
+    collect_positions(table, &mut positions);
+    positions.sort_unstable();
+    let mut positions = positions.into_iter();
+    assign_sorted_positions(table, &mut positions);
+}
+
+fn collect_positions(table: &Table, positions: &mut Vec<usize>) {
+    for (_, item) in table.iter() {
+        if let Item::Table(child) = item {
+            positions.extend(child.position());
+            collect_positions(child, positions);
+        }
+    }
+}
+
+fn assign_sorted_positions(table: &mut Table, positions: &mut impl Iterator<Item = usize>) {
+    table.sort_values();
+    for (_, item) in table.iter_mut() {
+        if let Item::Table(child) = item {
+            if child.position().is_some()
+                && let Some(position) = positions.next()
+            {
+                child.set_position(position);
+            }
+            assign_sorted_positions(child, positions);
+        }
+    }
+}
+
+/// Turn every sub-table of `table` into an inline table and sort the entries
+fn inline_entries(table: &mut Table) {
+    for (mut key, item) in table.iter_mut() {
+        if let Item::Table(entry) = item {
+            let inline = std::mem::take(entry).into_inline_table();
+            *item = Item::Value(Value::InlineTable(inline));
+            key.fmt();
+        }
+    }
+    table.sort_values();
+}
+
+/// Insert or replace `key` in a hand-written table.
+///
+/// A replaced value keeps its comments, and a table that was sorted by key stays sorted;
+/// otherwise new keys go last.
+pub fn insert_entry(table: &mut Table, key: &str, value: Value) {
+    let keys: Vec<&str> = table.iter().map(|(key, _)| key).collect();
+    let was_sorted = keys.is_sorted();
+
+    let mut value = value;
+    if let Some(current) = table.get(key).and_then(Item::as_value) {
+        *value.decor_mut() = current.decor().clone();
+    }
+    table.insert(key, Item::Value(value));
+
+    if was_sorted {
+        table.sort_values();
+    }
+}
+
+/// Get the table `key` of a hand-written document, creating it if needed
+pub fn table_mut<'a>(document: &'a mut DocumentMut, key: &str) -> Result<&'a mut Table> {
+    document
+        .entry(key)
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()
+        .with_context(|| format!("`{}` is not a table", key))
+}
+
+/// Merge the `managed_keys` of `generated` into `existing`.
+///
+/// Values that are semantically unchanged keep their original formatting, changed values
+/// keep the surrounding comments, and keys outside of `managed_keys` are not touched.
+pub fn merge_document(existing: &mut DocumentMut, generated: &DocumentMut, managed_keys: &[&str]) {
+    let mut merger = Merger {
+        next_position: max_position(existing.as_table()) + 1,
+    };
+    let existing = existing.as_table_mut();
+    for key in managed_keys {
+        match (existing.get_mut(key), generated.get(key)) {
+            (Some(current), Some(new)) => merger.merge_item(current, new),
+            (Some(_), None) => {
+                existing.remove(key);
+            }
+            (None, Some(new)) => {
+                let mut new = new.clone();
+                use_version_shorthand(&mut new);
+                merger.reposition(&mut new);
+                existing.insert(key, new);
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+struct Merger {
+    /// Position assigned to the next table inserted into the document
+    next_position: usize,
+}
+
+impl Merger {
+    fn merge_item(&mut self, current: &mut Item, new: &Item) {
+        if current.is_table_like() && new.is_table_like() {
+            let current = current.as_table_like_mut().unwrap();
+            let new = new.as_table_like().unwrap();
+            self.merge_table(current, new);
+            return;
+        }
+        if let (Some(current), Some(new)) =
+            (current.as_array_of_tables_mut(), new.as_array_of_tables())
+        {
+            while current.len() > new.len() {
+                current.remove(current.len() - 1);
+            }
+            for (idx, new_table) in new.iter().enumerate() {
+                match current.get_mut(idx) {
+                    Some(current_table) => self.merge_table(current_table, new_table),
+                    None => {
+                        let mut new_table = Item::Table(new_table.clone());
+                        use_version_shorthand(&mut new_table);
+                        self.reposition(&mut new_table);
+                        current.push(new_table.into_table().unwrap());
+                    }
+                }
+            }
+            return;
+        }
+        if let (Some(current_value), Some(new_value)) = (current.as_value(), new.as_value()) {
+            if values_equivalent(current_value, new_value) {
+                return;
+            }
+            let decor = current_value.decor().clone();
+            let mut new_value = Item::Value(new_value.clone());
+            use_version_shorthand(&mut new_value);
+            if let Some(new_value) = new_value.as_value_mut() {
+                *new_value.decor_mut() = decor;
+            }
+            *current = new_value;
+            return;
+        }
+        let mut new = new.clone();
+        use_version_shorthand(&mut new);
+        self.reposition(&mut new);
+        *current = new;
+    }
+
+    fn merge_table(&mut self, current: &mut dyn TableLike, new: &dyn TableLike) {
+        let removed: Vec<String> = current
+            .iter()
+            .map(|(key, _)| key.to_string())
+            .filter(|key| !new.contains_key(key))
+            .collect();
+        for key in removed {
+            current.remove(&key);
+        }
+        for (key, new_item) in new.iter() {
+            match current.get_mut(key) {
+                Some(current_item) => self.merge_item(current_item, new_item),
+                None => {
+                    let mut new_item = new_item.clone();
+                    use_version_shorthand(&mut new_item);
+                    self.reposition(&mut new_item);
+                    current.insert(key, new_item);
+                }
+            }
+        }
+    }
+
+    /// Give inserted tables positions after every existing table, keeping their relative order
+    fn reposition(&mut self, item: &mut Item) {
+        match item {
+            Item::Table(table) => {
+                table.set_position(self.next_position);
+                self.next_position += 1;
+                for (_, child) in table.iter_mut() {
+                    self.reposition(child);
+                }
+            }
+            Item::ArrayOfTables(tables) => {
+                for table in tables.iter_mut() {
+                    table.set_position(self.next_position);
+                    self.next_position += 1;
+                    for (_, child) in table.iter_mut() {
+                        self.reposition(child);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Write the entries holding only a `version` with the `name = "1"` shorthand, so values
+/// merged into a hand-written file look like the ones written by hand
+fn use_version_shorthand(item: &mut Item) {
+    match item {
+        Item::Value(value) => {
+            if let Value::InlineTable(table) = &*value
+                && table.len() == 1
+                && let Some(Value::String(version)) = table.get("version")
+            {
+                let mut shorthand = Value::from(version.value().as_str());
+                *shorthand.decor_mut() = value.decor().clone();
+                *value = shorthand;
+            }
+        }
+        Item::Table(table) => {
+            for (_, child) in table.iter_mut() {
+                use_version_shorthand(child);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn max_position(table: &Table) -> usize {
+    let mut max = table.position().unwrap_or(0);
+    for (_, item) in table.iter() {
+        match item {
+            Item::Table(child) => max = max.max(max_position(child)),
+            Item::ArrayOfTables(tables) => {
+                for child in tables.iter() {
+                    max = max.max(max_position(child));
+                }
+            }
+            _ => {}
+        }
+    }
+    max
+}
+
+/// Whether two values mean the same thing, ignoring formatting.
+///
+/// A version string is equivalent to an inline table holding only that `version`, which is
+/// how Cargo treats the dependency shorthand.
+fn values_equivalent(current: &Value, new: &Value) -> bool {
+    if let (Value::String(version), Value::InlineTable(table)) = (current, new)
+        && table.len() == 1
+        && let Some(Value::String(new_version)) = table.get("version")
+    {
+        return version.value() == new_version.value();
+    }
+    match (plain_value(current), plain_value(new)) {
+        (Some(current), Some(new)) => current == new,
+        _ => false,
+    }
+}
+
+fn plain_value(value: &Value) -> Option<toml::Value> {
+    let mut value = value.clone();
+    value.decor_mut().clear();
+    let mut table: toml::Table = toml::from_str(&format!("v = {}\n", value)).ok()?;
+    table.remove("v")
+}
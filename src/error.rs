@@ -0,0 +1,63 @@
+//! Errors that callers of magnet can tell apart
+//!
+//! Functions return `eyre::Result`; the failures listed here carry a [`MagnetError`] in the
+//! error chain, which can be recovered with [`MagnetError::find`] and decides the exit code
+//! of the CLI.
+
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// A failure of magnet that callers may want to handle
+#[derive(Debug, Error)]
+pub enum MagnetError {
+    /// No Magnet.toml or Cargo.toml was found where one is required
+    #[error("No Magnet.toml or Cargo.toml file found in {}", path.display())]
+    ManifestNotFound { path: PathBuf },
+    /// A Magnet.toml or Cargo.toml cannot be parsed or describes an invalid layout
+    #[error("Invalid manifest {}: {message}", path.display())]
+    InvalidManifest { path: PathBuf, message: String },
+    /// A dependency cannot be resolved to a local package
+    #[error("{message}")]
+    UnresolvedDependency { name: String, message: String },
+    /// A dependency matches several local packages or shared definitions
+    #[error("{message}")]
+    AmbiguousDependency { name: String, message: String },
+    /// The local package of a dependency does not satisfy its version requirement
+    #[error("{message}")]
+    VersionMismatch { name: String, message: String },
+    /// Generated Cargo.toml files differ from the ones on disk
+    #[error("{message}")]
+    OutOfDate {
+        files: Vec<PathBuf>,
+        message: String,
+    },
+    /// A git command failed
+    #[error("Git command `git {command}` failed in {}: {message}", path.display())]
+    Git {
+        command: String,
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl MagnetError {
+    /// Process exit code of the CLI for this error
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            MagnetError::ManifestNotFound { .. } => 3,
+            MagnetError::InvalidManifest { .. } => 4,
+            MagnetError::UnresolvedDependency { .. } => 5,
+            MagnetError::AmbiguousDependency { .. } => 6,
+            MagnetError::VersionMismatch { .. } => 7,
+            MagnetError::OutOfDate { .. } => 8,
+            MagnetError::Git { .. } => 9,
+        }
+    }
+
+    /// The first `MagnetError` in the chain of `report`, if any
+    pub fn find(report: &eyre::Report) -> Option<&MagnetError> {
+        report
+            .chain()
+            .find_map(|error| error.downcast_ref::<MagnetError>())
+    }
+}
@@ -1,48 +1,191 @@
 // filepath: /home/jakku/Dev/SHLL/crates/magnet/src/generator.rs
-use crate::configs::{ManifestConfig, PackageConfig, WorkspaceConfig};
+use crate::configs::{InheritableField, ManifestConfig, PackageConfig, WorkspaceConfig};
+use crate::document::{
+    MANAGED_KEYS, generated_header, is_generated, manifest_document, merge_document,
+    parse_document, strip_generated_header,
+};
 use crate::manager::ManifestManager;
-use crate::models::{PackageModel, WorkspaceModel};
+use crate::models::{
+    LintsMap, PackageModel, ProfileMap, WorkspaceModel, merge_lints, merge_profiles,
+    validate_features,
+};
+use crate::utils::diff_path;
 use eyre::{Context, Result};
-use tracing::info;
+use similar::TextDiff;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// A rendered Cargo.toml file that has not been written yet
+#[derive(Debug, Clone)]
+pub struct GeneratedFile {
+    /// Path the file is written to
+    pub path: PathBuf,
+    /// Generated content
+    pub content: String,
+}
+
+impl GeneratedFile {
+    /// Write the file to disk
+    pub fn write(&self) -> Result<()> {
+        std::fs::write(&self.path, &self.content)
+            .context(format!("Failed to write to {}", self.path.display()))
+    }
+
+    /// Unified diff between the file on disk and the generated content,
+    /// or `None` if they are identical.
+    ///
+    /// The generated-file headers are not compared, so a file generated by another version
+    /// of magnet is only out of date when its content differs.
+    pub fn diff(&self) -> Result<Option<String>> {
+        let current = if self.path.exists() {
+            std::fs::read_to_string(&self.path)
+                .context(format!("Failed to read {}", self.path.display()))?
+        } else {
+            String::new()
+        };
+        let same_content = is_generated(&current)
+            && strip_generated_header(&current) == strip_generated_header(&self.content);
+        if current == self.content || same_content {
+            return Ok(None);
+        }
+        let path = self.path.display().to_string();
+        let diff = TextDiff::from_lines(&current, &self.content)
+            .unified_diff()
+            .header(&path, &path)
+            .to_string();
+        Ok(Some(diff))
+    }
+}
 
 /// Cargo.toml generator
 pub struct CargoGenerator {
     /// Nexus manager
     nexus_manager: ManifestManager,
+    /// Edit existing Cargo.toml files in place instead of overwriting them
+    preserve_format: bool,
+    /// Write concrete values instead of `field.workspace = true` for inherited package fields
+    expand_workspace_fields: bool,
 }
 
 impl CargoGenerator {
     /// Create a new generator
     pub fn new(nexus_manager: ManifestManager) -> Self {
-        Self { nexus_manager }
+        Self {
+            nexus_manager,
+            preserve_format: false,
+            expand_workspace_fields: false,
+        }
+    }
+
+    /// Keep comments, key order and unmanaged sections of existing Cargo.toml files
+    pub fn with_preserve_format(mut self, preserve_format: bool) -> Self {
+        self.preserve_format = preserve_format;
+        self
+    }
+
+    /// Write the values inherited from `[workspace.package]` into each package
+    pub fn with_expand_workspace_fields(mut self, expand_workspace_fields: bool) -> Self {
+        self.expand_workspace_fields = expand_workspace_fields;
+        self
+    }
+
+    /// Render a manifest to the content that should be written to `cargo_toml_path`,
+    /// starting with a header naming `source_path` as the file it is generated from
+    pub fn render_manifest(
+        &self,
+        manifest: &ManifestConfig,
+        cargo_toml_path: &Path,
+        source_path: &Path,
+    ) -> Result<String> {
+        let generated = manifest_document(manifest)?;
+        let existing = if self.preserve_format && cargo_toml_path.exists() {
+            let content = std::fs::read_to_string(cargo_toml_path)
+                .context(format!("Failed to read {}", cargo_toml_path.display()))?;
+            let mut existing = parse_document(strip_generated_header(&content))
+                .context(format!("Failed to parse {}", cargo_toml_path.display()))?;
+            merge_document(&mut existing, &generated, MANAGED_KEYS);
+            Some(existing)
+        } else {
+            None
+        };
+        let body = existing.unwrap_or(generated).to_string();
+
+        // A Cargo.toml that is its own source is hand-written, so it gets no header
+        if cargo_toml_path == source_path {
+            return Ok(body);
+        }
+        let cargo_toml_dir = cargo_toml_path.parent().unwrap_or(Path::new("."));
+        let source = diff_path(cargo_toml_dir, source_path);
+        Ok(format!("{}\n{}", generated_header(&source), body))
     }
 
     /// Generate all Cargo.toml files for a specific workspace
     pub fn generate_all(&mut self, workspace: &WorkspaceModel) -> Result<()> {
-        // First, generate the root Cargo.toml
-        self.generate_workspace_cargo_toml(workspace)?;
+        for file in self.render_all(workspace)? {
+            info!("Generating Cargo.toml at {}", file.path.display());
+            file.write()?;
+        }
+
+        Ok(())
+    }
+
+    /// Render all Cargo.toml files for a specific workspace without writing them
+    pub fn render_all(&mut self, workspace: &WorkspaceModel) -> Result<Vec<GeneratedFile>> {
+        // First, render the root Cargo.toml
+        let mut files = vec![self.render_workspace_cargo_toml(workspace)?];
 
-        // Generate for all packages in the specified workspace
+        // Render for all packages in the specified workspace
+        let workspace_lints = self.workspace_lints(workspace);
         for mut package in workspace.list_packages()? {
-            self.generate_package_cargo_toml(&mut package)?;
+            // The root package shares the workspace Cargo.toml, rendered above
+            if package.root_path == workspace.root_path {
+                continue;
+            }
+            files.push(self.render_package_cargo_toml(&mut package, &workspace_lints)?);
         }
 
-        Ok(())
+        Ok(files)
+    }
+
+    /// Lints of a workspace, merged over the nexus-level defaults
+    fn workspace_lints(&self, workspace: &WorkspaceModel) -> LintsMap {
+        match self.nexus_manager.nexus() {
+            Some(nexus) => merge_lints(&nexus.lints, &workspace.lints),
+            None => workspace.lints.clone(),
+        }
+    }
+
+    /// Build profiles of a workspace, merged over the nexus-level defaults
+    fn workspace_profiles(&self, workspace: &WorkspaceModel) -> ProfileMap {
+        match self.nexus_manager.nexus() {
+            Some(nexus) => merge_profiles(&nexus.profile, &workspace.profile),
+            None => workspace.profile.clone(),
+        }
     }
 
     /// Generate a workspace manifest for a specific workspace
-    fn generate_workspace_manifest(&self, workspace: &WorkspaceModel) -> Result<ManifestConfig> {
+    fn generate_workspace_manifest(
+        &mut self,
+        workspace: &WorkspaceModel,
+    ) -> Result<ManifestConfig> {
         // Create a new manifest config
         let mut manifest = ManifestConfig::new();
 
+        // Shared definitions are resolved like the dependencies of packages, so that no
+        // Magnet-only key such as `nexus` or `fallback` reaches Cargo
+        let mut dependencies = self.nexus_manager.workspace_dependencies(workspace);
+        self.nexus_manager
+            .resolve_dependency_map(&workspace.root_path, &mut dependencies)?;
+
         // Set workspace configuration
         let workspace_config = WorkspaceConfig {
-            members: workspace.members.clone(),
+            members: workspace.cargo_members()?,
             exclude: workspace.exclude.clone(),
+            respect_gitignore: false,
             resolver: workspace.resolver.clone(),
-            dependencies: workspace
-                .dependencies
-                .clone()
+            package: workspace.package.clone(),
+            lints: self.workspace_lints(workspace),
+            dependencies: dependencies
                 .into_iter()
                 .map(|(k, v)| (k, v.into()))
                 .collect(),
@@ -51,6 +194,9 @@ impl CargoGenerator {
 
         manifest.workspace = Some(workspace_config);
 
+        // Cargo only reads profiles from the workspace root
+        manifest.profile = self.workspace_profiles(workspace);
+
         // Add the patch section if it exists in the original config
         manifest.patch = workspace.patch.clone();
 
@@ -58,63 +204,158 @@ impl CargoGenerator {
     }
 
     /// Generate the root Cargo.toml file for a specific workspace
-    pub fn generate_workspace_cargo_toml(&self, workspace: &WorkspaceModel) -> Result<()> {
+    pub fn generate_workspace_cargo_toml(&mut self, workspace: &WorkspaceModel) -> Result<()> {
+        let file = self.render_workspace_cargo_toml(workspace)?;
+        info!("Generating Cargo.toml at {}", file.path.display());
+        file.write()
+    }
+
+    /// Render the root Cargo.toml file for a specific workspace
+    pub fn render_workspace_cargo_toml(
+        &mut self,
+        workspace: &WorkspaceModel,
+    ) -> Result<GeneratedFile> {
         // Path to the root Cargo.toml
         let cargo_toml_path = workspace.root_path.join("Cargo.toml");
-        info!("Generating Cargo.toml at {}", cargo_toml_path.display());
 
         // Create a new workspace manifest using WorkspaceConfig
-        let manifest = self.generate_workspace_manifest(&workspace)?;
+        let mut manifest = self.generate_workspace_manifest(workspace)?;
+
+        // A root package is generated into the same Cargo.toml as the workspace
+        if workspace.root_package {
+            let mut package = PackageModel::from_dir(&workspace.root_path)?;
+            let workspace_lints = self.workspace_lints(workspace);
+            let package_manifest =
+                self.generate_package_manifest(&mut package, &workspace_lints)?;
+            manifest = ManifestConfig {
+                workspace: manifest.workspace,
+                profile: manifest.profile,
+                patch: manifest.patch,
+                ..package_manifest
+            };
+        }
 
         // Convert to TOML string
-        let toml_string = toml::to_string_pretty(&manifest)
+        let content = self
+            .render_manifest(&manifest, &cargo_toml_path, &workspace.source_path)
             .context("Failed to convert workspace manifest to TOML")?;
 
-        // Write to file
-        std::fs::write(&cargo_toml_path, toml_string)
-            .context(format!("Failed to write to {}", cargo_toml_path.display()))?;
-
-        Ok(())
+        Ok(GeneratedFile {
+            path: cargo_toml_path,
+            content,
+        })
     }
 
-    fn generate_package_cargo_toml(&mut self, package: &mut PackageModel) -> Result<()> {
+    fn render_package_cargo_toml(
+        &mut self,
+        package: &mut PackageModel,
+        workspace_lints: &LintsMap,
+    ) -> Result<GeneratedFile> {
         // Get the package path
         let package_path = package.root_path.as_path();
         // Path to the package Cargo.toml
         let cargo_toml_path = package_path.join("Cargo.toml");
 
         // Create a new package manifest
-        let package_manifest = self.generate_package_manifest(package)?;
+        let package_manifest = self.generate_package_manifest(package, workspace_lints)?;
 
         // Convert to TOML string
-        let toml_string = toml::to_string_pretty(&package_manifest)
+        let content = self
+            .render_manifest(&package_manifest, &cargo_toml_path, &package.source_path)
             .context("Failed to convert package manifest to TOML")?;
 
-        // Write to file
-        std::fs::write(&cargo_toml_path, toml_string)
-            .context(format!("Failed to write to {}", cargo_toml_path.display()))?;
+        Ok(GeneratedFile {
+            path: cargo_toml_path,
+            content,
+        })
+    }
 
-        Ok(())
+    /// Render the Cargo.toml of a root package on its own, without the workspace it shares
+    /// its manifest with
+    pub fn render_standalone_package_cargo_toml(
+        &mut self,
+        package: &mut PackageModel,
+        cargo_toml_path: &Path,
+    ) -> Result<GeneratedFile> {
+        let workspace = WorkspaceModel::from_dir(&package.root_path)?;
+        let workspace_lints = self.workspace_lints(&workspace);
+        let package_manifest = self.generate_package_manifest(package, &workspace_lints)?;
+
+        let content = self
+            .render_manifest(&package_manifest, cargo_toml_path, &package.source_path)
+            .context("Failed to convert package manifest to TOML")?;
+
+        Ok(GeneratedFile {
+            path: cargo_toml_path.to_path_buf(),
+            content,
+        })
     }
 
     /// Generate a crate manifest
-    fn generate_package_manifest(&mut self, model: &mut PackageModel) -> Result<ManifestConfig> {
+    fn generate_package_manifest(
+        &mut self,
+        model: &mut PackageModel,
+        workspace_lints: &LintsMap,
+    ) -> Result<ManifestConfig> {
+        validate_features(model)?;
         self.nexus_manager.resolve_package_dependencies(model)?;
+        if !model.profile.is_empty() {
+            warn!(
+                "Ignoring [profile] in {}: Cargo only reads profiles from the workspace root, \
+                 declare them in the workspace or nexus Magnet.toml instead",
+                model.source_path.display()
+            );
+        }
 
         // Create a new manifest config
         let mut manifest = ManifestConfig::new();
 
         // Create package section
+        // Inherited fields stay `field.workspace = true` unless they should be expanded
+        let expand = self.expand_workspace_fields;
+        let inherited = |name: &str| !expand && model.is_inherited(name);
         manifest.package = Some(PackageConfig {
             name: model.name.clone(),
-            version: model.version.clone(),
-            edition: Some(model.edition.clone()),
-            description: model.description.clone(),
-            license: model.license.clone(),
-            authors: model.authors.clone(),
-            homepage: model.homepage.clone(),
-            repository: model.repository.clone(),
-            documentation: model.documentation.clone(),
+            version: package_field(inherited("version"), model.version.clone()),
+            edition: Some(package_field(inherited("edition"), model.edition.clone())),
+            description: package_field(inherited("description"), model.description.clone()),
+            license: model
+                .license
+                .clone()
+                .map(|v| package_field(inherited("license"), v)),
+            license_file: model
+                .license_file
+                .clone()
+                .map(|v| package_field(inherited("license-file"), v)),
+            authors: package_field(inherited("authors"), model.authors.clone()),
+            homepage: model
+                .homepage
+                .clone()
+                .map(|v| package_field(inherited("homepage"), v)),
+            repository: model
+                .repository
+                .clone()
+                .map(|v| package_field(inherited("repository"), v)),
+            documentation: model
+                .documentation
+                .clone()
+                .map(|v| package_field(inherited("documentation"), v)),
+            rust_version: model
+                .rust_version
+                .clone()
+                .map(|v| package_field(inherited("rust-version"), v)),
+            keywords: model
+                .keywords
+                .clone()
+                .map(|v| package_field(inherited("keywords"), v)),
+            categories: model
+                .categories
+                .clone()
+                .map(|v| package_field(inherited("categories"), v)),
+            publish: model
+                .publish
+                .clone()
+                .map(|v| package_field(inherited("publish"), v)),
             custom: model.custom.clone(),
         });
 
@@ -125,6 +366,47 @@ impl CargoGenerator {
             .into_iter()
             .map(|(k, v)| (k, v.into()))
             .collect();
+        manifest.dev_dependencies = model
+            .dev_dependencies
+            .clone()
+            .into_iter()
+            .map(|(k, v)| (k, v.into()))
+            .collect();
+        manifest.build_dependencies = model
+            .build_dependencies
+            .clone()
+            .into_iter()
+            .map(|(k, v)| (k, v.into()))
+            .collect();
+        manifest.target = model
+            .target
+            .clone()
+            .into_iter()
+            .filter(|(_, v)| !v.is_empty())
+            .map(|(k, v)| (k, v.into()))
+            .collect();
+
+        // Features and build targets are passed through as-is
+        manifest.features = model.features.clone();
+        manifest.lib = model.lib.clone();
+        manifest.bin = model.bin.clone();
+        manifest.test = model.test.clone();
+        manifest.bench = model.bench.clone();
+        manifest.example = model.example.clone();
+
+        // Packages follow the workspace lints; their own lints override the shared ones
+        manifest.lints = match &model.lints {
+            None if workspace_lints.is_empty() => None,
+            None | Some(InheritableField::Inherited(_)) if !self.expand_workspace_fields => {
+                Some(InheritableField::inherited())
+            }
+            None | Some(InheritableField::Inherited(_)) => {
+                Some(InheritableField::Value(workspace_lints.clone()))
+            }
+            Some(InheritableField::Value(lints)) => {
+                Some(InheritableField::Value(merge_lints(workspace_lints, lints)))
+            }
+        };
 
         // Get the patch section if it exists in the source Magnet.toml file
         manifest.patch = model.patch.clone();
@@ -132,3 +414,12 @@ impl CargoGenerator {
         Ok(manifest)
     }
 }
+
+/// A package field that is either inherited from the workspace or set to `value`
+fn package_field<T>(inherited: bool, value: T) -> InheritableField<T> {
+    if inherited {
+        InheritableField::inherited()
+    } else {
+        InheritableField::Value(value)
+    }
+}
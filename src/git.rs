@@ -0,0 +1,118 @@
+//! Reading the state of local git repositories
+//!
+//! Used to turn local packages into git dependencies on the commit that is checked out, and to
+//! skip members ignored by git.
+
+use crate::MagnetError;
+use eyre::{Context, Result, bail, eyre};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tracing::{debug, warn};
+
+/// Where the code of a local checkout can be fetched from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitSource {
+    /// URL of the remote repository
+    pub url: String,
+    /// Commit checked out in the local repository
+    pub rev: String,
+    /// Tag pointing at the checked out commit, if any
+    pub tag: Option<String>,
+}
+
+impl GitSource {
+    /// Read the remote URL and the checked out commit of the repository containing `path`.
+    ///
+    /// The `origin` remote is preferred, otherwise the first configured remote is used.
+    pub fn from_dir(path: &Path) -> Result<Self> {
+        let remotes = git_output(path, &["remote"])?;
+        let remote = remotes
+            .lines()
+            .find(|remote| *remote == "origin")
+            .or_else(|| remotes.lines().next())
+            .ok_or_else(|| {
+                eyre!(
+                    "Git repository at {} has no remote to fetch it from",
+                    path.display()
+                )
+            })?;
+        let url = git_output(path, &["remote", "get-url", remote])?;
+        let rev = git_output(path, &["rev-parse", "HEAD"])?;
+        let tag = git_output(path, &["describe", "--tags", "--exact-match", "HEAD"]).ok();
+
+        if !git_output(path, &["status", "--porcelain"])?.is_empty() {
+            warn!(
+                "Git repository at {} has uncommitted changes, they are not part of commit {}",
+                path.display(),
+                rev
+            );
+        }
+
+        Ok(Self { url, rev, tag })
+    }
+}
+
+/// Paths among `paths` that are ignored by the git repository containing `root_path`
+pub fn ignored_paths(root_path: &Path, paths: &[PathBuf]) -> Result<HashSet<PathBuf>> {
+    if paths.is_empty() {
+        return Ok(HashSet::new());
+    }
+    debug!("Executing git command: git check-ignore --stdin");
+
+    let mut child = Command::new("git")
+        .current_dir(root_path)
+        .args(["check-ignore", "--stdin"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute git command: git check-ignore --stdin")?;
+    {
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        for path in paths {
+            writeln!(stdin, "{}", path.display())?;
+        }
+    }
+    let output = child.wait_with_output()?;
+
+    // Exit code 1 means that none of the paths is ignored
+    match output.status.code() {
+        Some(0) | Some(1) => {}
+        _ => bail!(MagnetError::Git {
+            command: "check-ignore --stdin".to_string(),
+            path: root_path.to_path_buf(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }),
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Run a git command in `path` and return its trimmed standard output
+fn git_output(path: &Path, args: &[&str]) -> Result<String> {
+    debug!("Executing git command: git {}", args.join(" "));
+
+    let output = Command::new("git")
+        .current_dir(path)
+        .args(args)
+        .output()
+        .context(format!(
+            "Failed to execute git command: git {}",
+            args.join(" ")
+        ))?;
+
+    if !output.status.success() {
+        bail!(MagnetError::Git {
+            command: args.join(" "),
+            path: path.to_path_buf(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
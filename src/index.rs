@@ -0,0 +1,137 @@
+//! Index of the packages and workspaces below a root manifest
+//!
+//! Listing packages globs and parses every Magnet.toml, so the manager builds this index once
+//! and only rebuilds it when one of the files or directories it was read from changes.
+
+use crate::models::{DependencyModel, ManifestModel, PackageModel, WorkspaceModel};
+use eyre::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Packages by name and workspace dependencies by name, read from a root manifest
+#[derive(Debug, Clone, Default)]
+pub struct ManifestIndex {
+    /// Packages by name; the same name may be found in several places
+    packages: HashMap<String, Vec<PackageModel>>,
+    /// Every workspace below the root manifest
+    workspaces: Vec<WorkspaceModel>,
+    /// Indices into `workspaces` of the workspaces declaring a dependency, by dependency name
+    workspace_dependencies: HashMap<String, Vec<usize>>,
+    /// Nexus members listed by path that are not checked out
+    missing_members: Vec<PathBuf>,
+    /// Modification times of the manifests and directories the index was built from;
+    /// `None` for paths that did not exist
+    sources: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+impl ManifestIndex {
+    /// Read every workspace and package below `root_manifest`, located at `root_path`
+    pub fn build(root_path: &Path, root_manifest: &ManifestModel) -> Result<Self> {
+        let mut index = Self::default();
+        index.track(root_path);
+        index.track(&root_path.join("Magnet.toml"));
+        index.track(&root_path.join("Cargo.toml"));
+
+        // A nexus is walked once for both its workspaces and its packages
+        let (workspaces, packages) = match root_manifest {
+            ManifestModel::Nexus(nexus) => {
+                let members = nexus.members()?;
+                index.missing_members = members.all_missing();
+                (members.all_workspaces(), members.all_packages()?)
+            }
+            _ => (
+                root_manifest.list_workspaces()?,
+                root_manifest.list_packages()?,
+            ),
+        };
+        for workspace in workspaces {
+            index.track(&workspace.source_path);
+            index.track(&workspace.root_path);
+            for name in workspace.dependencies.keys() {
+                index
+                    .workspace_dependencies
+                    .entry(name.clone())
+                    .or_default()
+                    .push(index.workspaces.len());
+            }
+            index.workspaces.push(workspace);
+        }
+        for package in packages {
+            index.track(&package.source_path);
+            if let Some(parent) = package.root_path.parent() {
+                // New packages next to this one change the directory
+                index.track(parent);
+            }
+            index
+                .packages
+                .entry(package.name.clone())
+                .or_default()
+                .push(package);
+        }
+
+        Ok(index)
+    }
+
+    /// Nexus members listed by path whose directory does not exist
+    pub fn missing_members(&self) -> &[PathBuf] {
+        &self.missing_members
+    }
+
+    /// Whether any manifest or directory the index was built from changed since
+    pub fn is_stale(&self) -> bool {
+        self.sources
+            .iter()
+            .any(|(path, modified)| &modified_time(path) != modified)
+    }
+
+    /// Packages with the given name
+    pub fn find_packages(&self, name: &str) -> &[PackageModel] {
+        self.packages
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Every indexed package
+    pub fn packages(&self) -> impl Iterator<Item = &PackageModel> {
+        self.packages.values().flatten()
+    }
+
+    /// Every indexed workspace
+    pub fn workspaces(&self) -> &[WorkspaceModel] {
+        &self.workspaces
+    }
+
+    /// The innermost workspace containing `path`
+    pub fn find_workspace_containing(&self, path: &Path) -> Option<&WorkspaceModel> {
+        self.workspaces
+            .iter()
+            .filter(|workspace| path.starts_with(&workspace.root_path))
+            .max_by_key(|workspace| workspace.root_path.components().count())
+    }
+
+    /// Workspaces declaring a shared dependency with the given name, with its definition
+    pub fn find_workspace_dependencies(
+        &self,
+        name: &str,
+    ) -> impl Iterator<Item = (&WorkspaceModel, &DependencyModel)> {
+        self.workspace_dependencies
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(move |&idx| {
+                let workspace = &self.workspaces[idx];
+                (workspace, &workspace.dependencies[name])
+            })
+    }
+
+    fn track(&mut self, path: &Path) {
+        self.sources.insert(path.to_path_buf(), modified_time(path));
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
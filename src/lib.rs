@@ -23,19 +23,29 @@
 //! ## Core modules
 //!
 //! - `config`: Configuration handling for Magnet.toml files
+//! - `document`: Format-preserving editing of TOML files
+//! - `error`: Errors callers can tell apart, and the exit codes they map to
 //! - `manager`: Workspace discovery and management
+//! - `index`: Cached index of the packages and workspaces of a manifest
 //! - `generator`: Cargo.toml generation from Magnet configuration
+//! - `git`: Reading the state of local git repositories
 //! - `resolver`: Dependency resolution across workspaces
 //! - `commands`: CLI command implementations
 
 // Public modules
 pub mod commands;
 pub mod configs;
+pub mod document;
+pub mod error;
 pub mod generator;
+pub mod git;
+pub mod index;
 pub mod manager;
 pub mod models;
 pub mod utils;
 
+pub use error::MagnetError;
+
 // Export version information
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const AUTHOR: &str = env!("CARGO_PKG_AUTHORS");
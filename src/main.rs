@@ -1,14 +1,50 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use eyre::{Context, Result};
 use std::path::PathBuf;
+use std::process::ExitCode;
 use tracing::{debug, info};
 
 // Use local utils module instead of common crate
-use magnet::commands::{self, generate::GenerateOptions};
+use magnet::MagnetError;
+use magnet::commands::{
+    self,
+    add::{AddOptions, AddSource},
+    generate::GenerateOptions,
+    new::NewOptions,
+    remove::RemoveOptions,
+};
+use magnet::manager::ResolutionMode;
+use magnet::models::DependencyKind;
 use magnet::utils::{LogLevel, setup_logs};
 
 /// CLI entry point
-fn main() -> Result<()> {
+///
+/// Exit codes:
+///
+/// | Code | Meaning                                                        |
+/// |------|----------------------------------------------------------------|
+/// | 0    | Success                                                        |
+/// | 1    | Any other error                                                |
+/// | 2    | Invalid command line arguments                                 |
+/// | 3    | No Magnet.toml or Cargo.toml found                             |
+/// | 4    | Invalid Magnet.toml or Cargo.toml                              |
+/// | 5    | A dependency cannot be resolved                                |
+/// | 6    | A dependency matches several local packages                    |
+/// | 7    | A local package does not satisfy the version of its dependency |
+/// | 8    | Cargo.toml files are out of date (`generate --check`)          |
+/// | 9    | A git command failed                                           |
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(report) => {
+            eprintln!("Error: {:?}", report);
+            let code = MagnetError::find(&report).map_or(1, MagnetError::exit_code);
+            ExitCode::from(code)
+        }
+    }
+}
+
+fn run() -> Result<()> {
     let cli = Cli::parse();
 
     // Setup logging based on verbosity
@@ -35,6 +71,12 @@ fn main() -> Result<()> {
             copy_lock,
             include_cargo_dir,
             symlink_cargo_dir,
+            preserve_format,
+            expand_workspace_fields,
+            check,
+            mode,
+            keep_path,
+            strict,
         }) => {
             let options = GenerateOptions {
                 config_path: config,
@@ -42,10 +84,100 @@ fn main() -> Result<()> {
                 copy_lock,
                 include_cargo_dir,
                 symlink_cargo_dir,
+                preserve_format,
+                expand_workspace_fields,
+                check,
+                mode: mode.into(),
+                keep_path,
+                strict,
             };
             commands::generate(&options)
         }
-        Some(Commands::Check { config }) => commands::check(&config),
+        Some(Commands::New {
+            name,
+            lib,
+            bin: _,
+            workspace,
+        }) => {
+            let options = NewOptions {
+                name,
+                lib,
+                workspace,
+                ..Default::default()
+            };
+            commands::new(&options)
+        }
+        Some(Commands::Add {
+            dependency,
+            nexus,
+            workspace,
+            path,
+            git,
+            dev,
+            build,
+            features,
+            package,
+            generate,
+        }) => {
+            let (name, version) = match dependency.split_once('@') {
+                Some((name, version)) => (name.to_string(), Some(version.to_string())),
+                None => (dependency, None),
+            };
+            let source = if nexus {
+                AddSource::Nexus
+            } else if workspace {
+                AddSource::Workspace
+            } else if let Some(path) = path {
+                AddSource::Path(path)
+            } else if let Some(git) = git {
+                AddSource::Git(git)
+            } else {
+                AddSource::Registry
+            };
+            let kind = if dev {
+                DependencyKind::Dev
+            } else if build {
+                DependencyKind::Build
+            } else {
+                DependencyKind::Normal
+            };
+            let options = AddOptions {
+                name,
+                version,
+                source,
+                kind,
+                features,
+                package,
+                generate,
+                ..Default::default()
+            };
+            commands::add(&options)
+        }
+        Some(Commands::Remove {
+            dependency,
+            dev,
+            build,
+            package,
+            prune,
+        }) => {
+            let kind = if dev {
+                DependencyKind::Dev
+            } else if build {
+                DependencyKind::Build
+            } else {
+                DependencyKind::Normal
+            };
+            let options = RemoveOptions {
+                name: dependency,
+                kind,
+                package,
+                prune,
+                ..Default::default()
+            };
+            commands::remove(&options)
+        }
+        Some(Commands::Import { path }) => commands::import(&path),
+        Some(Commands::Check { config, strict }) => commands::check(&config, strict),
         Some(Commands::Tree { config }) => commands::tree(&config),
         Some(Commands::Export {
             package,
@@ -55,6 +187,9 @@ fn main() -> Result<()> {
             symlink_cargo_dir,
             export_dir,
             crates_dir,
+            mode,
+            keep_path,
+            strict,
         }) => {
             let options = commands::export::ExportOptions {
                 package_path: package,
@@ -64,6 +199,9 @@ fn main() -> Result<()> {
                 symlink_cargo_dir,
                 export_dir,
                 crates_dir,
+                mode: mode.into(),
+                keep_path,
+                strict,
             };
             commands::export(&options)
         }
@@ -132,12 +270,130 @@ enum Commands {
         /// Create symlinks for .cargo directory instead of copying
         #[arg(long, default_value_t = true)]
         symlink_cargo_dir: bool,
+
+        /// Edit existing Cargo.toml files in place, keeping comments, key order and
+        /// sections Magnet does not manage
+        #[arg(long)]
+        preserve_format: bool,
+
+        /// Write the values inherited from [workspace.package] into each package instead of
+        /// keeping `field.workspace = true`
+        #[arg(long)]
+        expand_workspace_fields: bool,
+
+        /// Do not write anything; print a diff and fail if any Cargo.toml is out of date
+        #[arg(long)]
+        check: bool,
+
+        /// How `nexus = true` dependencies are written: relative paths, versions that can be
+        /// published, or git repositories at the checked out commit
+        #[arg(long, value_enum, default_value_t)]
+        mode: Mode,
+
+        /// With `--mode publish`, keep the `path` next to the `version`
+        #[arg(long)]
+        keep_path: bool,
+
+        /// Fail on dependencies that cannot be resolved instead of leaving them as-is
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Create a package with a Magnet.toml that inherits the workspace and nexus defaults
+    New {
+        /// Name of the package, created in the current directory unless --workspace is given
+        name: String,
+
+        /// Create a library package
+        #[arg(long, conflicts_with = "bin")]
+        lib: bool,
+
+        /// Create a binary package (default)
+        #[arg(long)]
+        bin: bool,
+
+        /// Workspace of the nexus to create the package in; a new workspace with this name is
+        /// scaffolded and added to the nexus if none exists
+        #[arg(long)]
+        workspace: Option<String>,
+    },
+    /// Add a dependency to the Magnet.toml of the current package
+    Add {
+        /// Name of the dependency, optionally with a version requirement (`name@1.2`)
+        dependency: String,
+
+        /// Take the dependency from the packages of the nexus (`nexus = true`)
+        #[arg(long, group = "source")]
+        nexus: bool,
+
+        /// Use the definition shared by the workspace (`workspace = true`)
+        #[arg(long, group = "source")]
+        workspace: bool,
+
+        /// Take the dependency from a local directory
+        #[arg(long, group = "source")]
+        path: Option<PathBuf>,
+
+        /// Take the dependency from a git repository
+        #[arg(long, group = "source")]
+        git: Option<String>,
+
+        /// Add to [dev-dependencies]
+        #[arg(long)]
+        dev: bool,
+
+        /// Add to [build-dependencies]
+        #[arg(long, conflicts_with = "dev")]
+        build: bool,
+
+        /// Features to enable, separated by commas
+        #[arg(short = 'F', long, value_delimiter = ',')]
+        features: Vec<String>,
+
+        /// Package to add the dependency to (default: the package in the current directory)
+        #[arg(short, long)]
+        package: Option<String>,
+
+        /// Generate the Cargo.toml files of the workspace afterwards
+        #[arg(long)]
+        generate: bool,
+    },
+    /// Remove a dependency from the Magnet.toml of the current package
+    Remove {
+        /// Name of the dependency
+        dependency: String,
+
+        /// Remove from [dev-dependencies]
+        #[arg(long)]
+        dev: bool,
+
+        /// Remove from [build-dependencies]
+        #[arg(long, conflicts_with = "dev")]
+        build: bool,
+
+        /// Package to remove the dependency from (default: the package in the current directory)
+        #[arg(short, long)]
+        package: Option<String>,
+
+        /// Also remove the workspace- or nexus-level definition the dependency inherited when
+        /// no package uses it any more
+        #[arg(long)]
+        prune: bool,
+    },
+    /// Convert the Cargo workspaces and packages below a directory into a nexus
+    Import {
+        /// Directory holding the repositories to import
+        #[arg(default_value = ".")]
+        path: PathBuf,
     },
     /// Check Magnet.toml for issues
     Check {
         /// Path to the Magnet.toml file
         #[arg(default_value = ".")]
         config: PathBuf,
+
+        /// Fail on dependencies that cannot be resolved instead of only warning
+        #[arg(long)]
+        strict: bool,
     },
     /// Display workspace hierarchy as a tree
     Tree {
@@ -174,6 +430,19 @@ enum Commands {
         /// Subdirectory name for exported crates (default: "crates")
         #[arg(short = 'd', long, default_value = "crates")]
         crates_dir: String,
+
+        /// How dependencies on exported crates are written: relative paths, versions that can
+        /// be published, or git repositories at the checked out commit
+        #[arg(long, value_enum, default_value_t)]
+        mode: Mode,
+
+        /// With `--mode publish`, keep the `path` next to the `version`
+        #[arg(long)]
+        keep_path: bool,
+
+        /// Fail on dependencies that cannot be resolved instead of leaving them as-is
+        #[arg(long)]
+        strict: bool,
     },
     /// Manage git submodules
     Submodule {
@@ -210,3 +479,25 @@ enum SubmoduleAction {
         rev: String,
     },
 }
+
+/// Values of `--mode`, mapped to the library's `ResolutionMode`
+#[derive(Clone, Copy, Default, ValueEnum)]
+enum Mode {
+    /// Relative `path` dependencies, for local development
+    #[default]
+    Path,
+    /// `version` requirements on the local package versions, so the manifests can be published
+    Publish,
+    /// `git` dependencies on the commit (or tag) checked out in the package's repository
+    Git,
+}
+
+impl From<Mode> for ResolutionMode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Path => ResolutionMode::Path,
+            Mode::Publish => ResolutionMode::Publish,
+            Mode::Git => ResolutionMode::Git,
+        }
+    }
+}
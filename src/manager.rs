@@ -3,11 +3,43 @@
 //! This module handles workspace discovery, relationship management,
 //! and tracking crates across projects in a nexus.
 
-use crate::models::{DependencyModel, DependencyModelMap, ManifestModel, PackageModel, WorkspaceModel};
+use crate::MagnetError;
+use crate::git::GitSource;
+use crate::index::ManifestIndex;
+use crate::models::{
+    DependencyKind, DependencyModel, DependencyModelMap, DependencySource, ManifestModel,
+    NexusModel, PackageModel, WorkspaceModel,
+};
 use crate::utils::{diff_path, find_furthest_manifest};
-use eyre::{Result, bail};
+use eyre::{Result, WrapErr, bail};
+use semver::{Version, VersionReq};
+use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
-use tracing::warn;
+use std::sync::{Arc, Mutex, PoisonError};
+use tracing::{debug, info, warn};
+
+/// How dependencies on packages found in the nexus are written into generated manifests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResolutionMode {
+    /// Relative `path` dependencies, for local development
+    #[default]
+    Path,
+    /// `version` requirements on the local package versions, so the manifests can be published
+    Publish,
+    /// `git` dependencies on the commit (or tag) checked out in the package's repository
+    Git,
+}
+
+/// A dependency resolved from a later source of its `fallback` chain, for instance because
+/// the nexus member holding the package is not checked out
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FallbackUse {
+    /// Directory of the manifest declaring the dependency
+    pub manifest_root_path: PathBuf,
+    pub name: String,
+    /// Source the dependency was resolved from
+    pub source: DependencySource,
+}
 
 /// Nexus manager
 #[derive(Debug, Clone)]
@@ -15,6 +47,19 @@ pub struct ManifestManager {
     /// Path to the nexus root directory
     pub root_path: PathBuf,
     pub root_manifest: ManifestModel,
+    /// How `nexus = true` dependencies are resolved
+    pub mode: ResolutionMode,
+    /// In publish mode, keep the `path` next to the `version`
+    pub keep_path: bool,
+    /// Fail on dependencies that cannot be resolved instead of leaving them as-is
+    pub strict: bool,
+    /// Packages and workspaces below the root manifest, built on first use
+    index: Option<ManifestIndex>,
+    /// Dependencies resolved from fallback sources; shared by clones of the manager, so
+    /// generators working on a copy report into the same run
+    fallbacks: Arc<Mutex<BTreeSet<FallbackUse>>>,
+    /// Nexus members found not checked out while building the index, shared like `fallbacks`
+    missing_members: Arc<Mutex<BTreeSet<PathBuf>>>,
 }
 
 impl ManifestManager {
@@ -26,31 +71,335 @@ impl ManifestManager {
         let manager = Self {
             root_path,
             root_manifest: model,
+            mode: ResolutionMode::default(),
+            keep_path: false,
+            strict: false,
+            index: None,
+            fallbacks: Arc::default(),
+            missing_members: Arc::default(),
         };
 
         Ok(manager)
     }
 
-    /// Get a workspace by name
-    pub fn get_workspace(&self, workspace_name: &str) -> Option<WorkspaceModel> {
-        let workspaces = self.root_manifest.list_workspaces().ok()?;
-        for workspace in workspaces {
-            if workspace.name == workspace_name {
-                return Some(workspace.clone());
+    /// Set how `nexus = true` dependencies are resolved
+    pub fn with_mode(mut self, mode: ResolutionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// In publish mode, keep the `path` next to the `version` of local dependencies
+    pub fn with_keep_path(mut self, keep_path: bool) -> Self {
+        self.keep_path = keep_path;
+        self
+    }
+
+    /// Fail on dependencies that cannot be resolved instead of leaving them as-is
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Dependencies resolved from a fallback source so far
+    pub fn fallbacks(&self) -> Vec<FallbackUse> {
+        let fallbacks = self
+            .fallbacks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        fallbacks.iter().cloned().collect()
+    }
+
+    /// Nexus members found not checked out so far
+    pub fn missing_members(&self) -> Vec<PathBuf> {
+        let missing = self
+            .missing_members
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        missing.iter().cloned().collect()
+    }
+
+    /// Log the nexus members that are not checked out and the dependencies resolved from a
+    /// fallback source, if any
+    pub fn report_fallbacks(&self) {
+        let missing = self.missing_members();
+        if !missing.is_empty() {
+            warn!(
+                "{} nexus members are not checked out, dependencies on their packages use their fallback sources:",
+                missing.len()
+            );
+            for member in missing {
+                warn!("  {}", member.display());
             }
         }
-        None
+        let fallbacks = self.fallbacks();
+        if fallbacks.is_empty() {
+            return;
+        }
+        warn!(
+            "{} dependencies were resolved from fallback sources:",
+            fallbacks.len()
+        );
+        for fallback in fallbacks {
+            warn!(
+                "  {} of {}: {}",
+                fallback.name,
+                fallback.manifest_root_path.display(),
+                fallback.source
+            );
+        }
+    }
+
+    /// Get the nexus this manager is rooted at, if any
+    pub fn nexus(&self) -> Option<&NexusModel> {
+        match &self.root_manifest {
+            ManifestModel::Nexus(nexus) => Some(nexus),
+            _ => None,
+        }
+    }
+
+    /// Get the index of the packages and workspaces below the root manifest.
+    ///
+    /// The index is built on first use and rebuilt when a manifest or directory it was read
+    /// from has been modified since, together with the root manifest, whose `members` and
+    /// shared dependencies the index is built from.
+    pub fn index(&mut self) -> Result<&ManifestIndex> {
+        if self.index.as_ref().is_some_and(ManifestIndex::is_stale) {
+            let (root_path, root_manifest) = find_furthest_manifest(&self.root_path)?;
+            self.root_path = root_path;
+            self.root_manifest = root_manifest;
+            self.index = None;
+        }
+        self.cached_index()
+    }
+
+    /// Get the index without checking whether it is stale, which is cheap enough to do for
+    /// every dependency
+    fn cached_index(&mut self) -> Result<&ManifestIndex> {
+        if self.index.is_none() {
+            let index = ManifestIndex::build(&self.root_path, &self.root_manifest)?;
+            self.missing_members
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .extend(index.missing_members().iter().cloned());
+            self.index = Some(index);
+        }
+        Ok(self.index.as_ref().unwrap())
+    }
+
+    /// Get a workspace by name
+    pub fn get_workspace(&mut self, workspace_name: &str) -> Option<WorkspaceModel> {
+        let index = self.index().ok()?;
+        index
+            .workspaces()
+            .iter()
+            .find(|workspace| workspace.name == workspace_name)
+            .cloned()
     }
 
     /// Get dependencies for a specific workspace
-    pub fn get_workspace_dependencies(&self, workspace_name: &str) -> DependencyModelMap {
+    pub fn get_workspace_dependencies(&mut self, workspace_name: &str) -> DependencyModelMap {
         match self.get_workspace(workspace_name) {
-            Some(ws) => ws.dependencies.clone(),
+            Some(ws) => self.workspace_dependencies(&ws),
             None => DependencyModelMap::new(),
         }
     }
 
-    /// Resolve a dependency
+    /// Shared dependencies of a workspace: the nexus-level `[dependencies]` overridden by the
+    /// workspace's own `[workspace.dependencies]`, with paths relative to the workspace root
+    pub fn workspace_dependencies(&self, workspace: &WorkspaceModel) -> DependencyModelMap {
+        let mut dependencies = DependencyModelMap::new();
+        if let Some(nexus) = self.nexus() {
+            for (name, dep) in &nexus.dependencies {
+                let dep = rebase_dependency(dep, &nexus.root_path, &workspace.root_path);
+                dependencies.insert(name.clone(), dep);
+            }
+        }
+        dependencies.extend(workspace.dependencies.clone());
+        dependencies
+    }
+
+    /// Find the shared definition a `workspace = true` dependency of the manifest at
+    /// `manifest_root_path` inherits.
+    ///
+    /// Returns the directory of the workspace or nexus defining it, the name it is defined
+    /// under, which is the `package` name of a renamed dependency defined under its real
+    /// name, and the definition.
+    pub fn find_shared_dependency(
+        &mut self,
+        manifest_root_path: &Path,
+        name: &str,
+        dep: &DependencyModel,
+    ) -> Result<Option<(PathBuf, String, DependencyModel)>> {
+        if let Some((root_path, definition)) =
+            self.find_workspace_dependency(manifest_root_path, name)?
+        {
+            return Ok(Some((root_path, name.to_string(), definition)));
+        }
+        let Some(package) = &dep.package else {
+            return Ok(None);
+        };
+        Ok(self
+            .find_workspace_dependency(manifest_root_path, package)?
+            .map(|(root_path, definition)| (root_path, package.clone(), definition)))
+    }
+
+    /// Find the shared definition of a `workspace = true` dependency, together with the
+    /// directory its paths are relative to.
+    ///
+    /// The workspace containing the manifest is searched first, then the nexus-level
+    /// `[dependencies]` it inherits, then the other workspaces of the nexus.
+    fn find_workspace_dependency(
+        &mut self,
+        manifest_root_path: &Path,
+        name: &str,
+    ) -> Result<Option<(PathBuf, DependencyModel)>> {
+        let index = self.cached_index()?;
+        let own_workspace = index
+            .find_workspace_containing(manifest_root_path)
+            .map(|workspace| (workspace.root_path.clone(), workspace.find_dependency(name)));
+        let mut matching_crates: Vec<_> = index
+            .find_workspace_dependencies(name)
+            .map(|(workspace, dep)| (workspace.root_path.clone(), dep.clone()))
+            .collect();
+        let nexus_dependency = self.nexus().and_then(|nexus| {
+            let dep = nexus.dependencies.get(name)?;
+            Some((nexus.root_path.clone(), dep.clone()))
+        });
+
+        if let Some((root_path, dependency)) = own_workspace {
+            if let Some(dep) = dependency {
+                return Ok(Some((root_path, dep)));
+            }
+            if nexus_dependency.is_some() {
+                return Ok(nexus_dependency);
+            }
+        }
+
+        if matching_crates.len() > 1 {
+            let candidates: Vec<String> = matching_crates
+                .iter()
+                .map(|(root_path, _)| format!("  - {}", root_path.display()))
+                .collect();
+            bail!(MagnetError::AmbiguousDependency {
+                name: name.to_string(),
+                message: format!(
+                    "Dependency '{}' of {} is defined by several workspaces:\n{}\nDefine it in the [dependencies] of the nexus or in the workspace containing {}",
+                    name,
+                    manifest_root_path.display(),
+                    candidates.join("\n"),
+                    manifest_root_path.display()
+                ),
+            })
+        }
+        Ok(matching_crates.pop().or(nexus_dependency))
+    }
+
+    /// Find the package a `nexus` dependency refers to, by its `package` name when the
+    /// dependency is renamed.
+    ///
+    /// With `nexus = "<workspace>"` only that member workspace is searched. Otherwise, when
+    /// several packages share the name, the first workspace of the nexus `priority` list
+    /// holding one of them wins.
+    fn find_nexus_package(
+        &mut self,
+        manifest_root_path: &Path,
+        name: &str,
+        dep: &DependencyModel,
+    ) -> Result<Option<PackageModel>> {
+        let package_name = dep.package.as_deref().unwrap_or(name);
+        let nexus = self.nexus().cloned();
+        let index = self.cached_index()?;
+        let candidates: Vec<(&PackageModel, Option<&WorkspaceModel>)> = index
+            .find_packages(package_name)
+            .iter()
+            .map(|package| (package, index.find_workspace_containing(&package.root_path)))
+            .collect();
+        let refers_to =
+            |reference: &str, workspace: Option<&WorkspaceModel>| match (&nexus, workspace) {
+                (Some(nexus), Some(workspace)) => nexus.refers_to(reference, workspace),
+                (None, Some(workspace)) => workspace.name == reference,
+                (_, None) => false,
+            };
+
+        if let Some(reference) = dep.nexus_workspace() {
+            let mut selected: Vec<_> = candidates
+                .iter()
+                .filter(|(_, workspace)| refers_to(reference, *workspace))
+                .collect();
+            return match selected.pop() {
+                Some((package, _)) if selected.is_empty() => Ok(Some((*package).clone())),
+                Some(_) => bail!(MagnetError::AmbiguousDependency {
+                    name: name.to_string(),
+                    message: format!(
+                        "Workspace '{}' holds several packages named '{}':\n{}",
+                        reference,
+                        package_name,
+                        describe_candidates(&candidates)
+                    ),
+                }),
+                None => bail!(MagnetError::UnresolvedDependency {
+                    name: name.to_string(),
+                    message: format!(
+                        "Dependency '{}' of {} uses nexus = \"{}\", but that workspace has no package named '{}'{}",
+                        name,
+                        manifest_root_path.display(),
+                        reference,
+                        package_name,
+                        if candidates.is_empty() {
+                            String::new()
+                        } else {
+                            format!(", candidates are:\n{}", describe_candidates(&candidates))
+                        }
+                    ),
+                }),
+            };
+        }
+
+        match candidates.as_slice() {
+            [] => return Ok(None),
+            [(package, _)] => return Ok(Some((*package).clone())),
+            _ => {}
+        }
+        for reference in nexus.iter().flat_map(|nexus| &nexus.priority) {
+            let preferred: Vec<_> = candidates
+                .iter()
+                .filter(|(_, workspace)| refers_to(reference, *workspace))
+                .collect();
+            if let [(package, _)] = preferred.as_slice() {
+                return Ok(Some((*package).clone()));
+            }
+        }
+
+        let workspace = candidates
+            .iter()
+            .find_map(|(_, workspace)| workspace.map(|workspace| workspace.name.as_str()))
+            .unwrap_or("<workspace>");
+        let example = match &dep.package {
+            Some(package) => format!(
+                "{} = {{ package = \"{}\", nexus = \"{}\" }}",
+                name, package, workspace
+            ),
+            None => format!("{} = {{ nexus = \"{}\" }}", name, workspace),
+        };
+        bail!(MagnetError::AmbiguousDependency {
+            name: name.to_string(),
+            message: format!(
+                "Multiple packages named '{}' found for dependency '{}' of {}:\n{}\nPick one with `{}`, or list the preferred workspaces in `priority` under [nexus]",
+                package_name,
+                name,
+                manifest_root_path.display(),
+                describe_candidates(&candidates),
+                example
+            ),
+        })
+    }
+
+    /// Resolve a dependency.
+    ///
+    /// The sources listed in its `fallback` chain are tried in order; without a chain,
+    /// `workspace = true` uses the shared definition and `nexus = true` a package of the
+    /// nexus. A dependency no source applies to is left as-is with a warning, or fails in
+    /// strict mode.
     pub fn resolve_dependency(
         &mut self,
         manifest_root_path: &Path,
@@ -58,93 +407,384 @@ impl ManifestManager {
         dep: &DependencyModel,
     ) -> Result<DependencyModel> {
         let mut dep = dep.clone();
-        // If nexus is set to true, try to find the dependency in the nexus
-        if dep.nexus() {
-            // Auto-discovery: try to find the dependency in any workspace
-            let mut matching_crates = Vec::new();
-
-            // Then check in other workspaces
-            for pkg in self.root_manifest.list_packages()? {
-                if pkg.name == name {
-                    matching_crates.push(pkg.clone());
-                }
+        // Magnet-only settings, Cargo does not know about them
+        let fallback = dep.fallback.take();
+        let sources = match &fallback {
+            Some(sources) => sources.clone(),
+            None if dep.workspace() => vec![DependencySource::Workspace],
+            None if dep.nexus() => vec![DependencySource::Nexus],
+            None => {
+                dep.version_check = None;
+                return Ok(dep);
             }
+        };
 
-            if matching_crates.len() > 1 {
-                bail!(
-                    "Multiple matching crates found for dependency '{}': {:?}",
+        for (idx, source) in sources.iter().enumerate() {
+            let has_next = idx + 1 < sources.len();
+            let Some(mut resolved) =
+                self.resolve_from(*source, manifest_root_path, name, &dep, has_next)?
+            else {
+                continue;
+            };
+            resolved.version_check = None;
+            if idx > 0 {
+                let skipped: Vec<String> = sources[..idx].iter().map(ToString::to_string).collect();
+                debug!(
+                    "Dependency '{}' of {} resolved from fallback source {}, {} not available",
                     name,
-                    matching_crates
-                )
-            } else if matching_crates.len() == 0 {
-                warn!("No matching crates found for dependency '{}'", name);
-                return Ok(dep);
+                    manifest_root_path.display(),
+                    source,
+                    skipped.join(", ")
+                );
+                self.fallbacks
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .insert(FallbackUse {
+                        manifest_root_path: manifest_root_path.to_path_buf(),
+                        name: name.to_string(),
+                        source: *source,
+                    });
+            } else if fallback.is_some() {
+                info!(
+                    "Dependency '{}' of {} resolved from {}",
+                    name,
+                    manifest_root_path.display(),
+                    source
+                );
+            } else {
+                debug!(
+                    "Dependency '{}' of {} resolved from {}",
+                    name,
+                    manifest_root_path.display(),
+                    source
+                );
             }
-            dep.path = Some(diff_path(manifest_root_path, &matching_crates[0].root_path));
-            dep.nexus = None;
-            dep.workspace = None;
-            return Ok(dep);
+            return Ok(resolved);
         }
 
-        if dep.workspace() {
-            let mut matching_crates = Vec::new();
+        let tried: Vec<String> = sources.iter().map(ToString::to_string).collect();
+        let message = format!(
+            "Dependency '{}' of {} could not be resolved from {}",
+            name,
+            manifest_root_path.display(),
+            tried.join(", ")
+        );
+        if self.strict {
+            bail!(MagnetError::UnresolvedDependency {
+                name: name.to_string(),
+                message,
+            });
+        }
+        warn!("{}", message);
+        dep.version_check = None;
+        Ok(dep)
+    }
 
-            // Then check in other workspaces
-            for workspace in self.root_manifest.list_workspaces()? {
-                let Some(dep1) = workspace.find_dependency(name) else {
-                    continue;
+    /// Resolve a dependency from one source, or `None` if the source does not apply.
+    ///
+    /// A local package that does not satisfy the version requirement is skipped when
+    /// `has_next` says another source can be tried.
+    fn resolve_from(
+        &mut self,
+        source: DependencySource,
+        manifest_root_path: &Path,
+        name: &str,
+        dep: &DependencyModel,
+        has_next: bool,
+    ) -> Result<Option<DependencyModel>> {
+        let mut dep = dep.clone();
+        match source {
+            DependencySource::Workspace => {
+                let Some((base_path, _, definition)) =
+                    self.find_shared_dependency(manifest_root_path, name, &dep)?
+                else {
+                    return Ok(None);
                 };
-                matching_crates.push((workspace, dep1.clone()));
-            }
-
-            if matching_crates.len() > 1 {
-                bail!(
-                    "Multiple matching crates found for dependency '{}': {:?}",
+                let mut inherited =
+                    inherit_dependency(&dep, &definition, &base_path, manifest_root_path);
+                inherited.fallback = None;
+                if !inherited.nexus() {
+                    return Ok(Some(inherited));
+                }
+                self.resolve_from(
+                    DependencySource::Nexus,
+                    manifest_root_path,
                     name,
-                    matching_crates
+                    &inherited,
+                    has_next,
                 )
-            } else if matching_crates.len() == 0 {
-                warn!("No matching crates found for dependency '{}'", name);
-                return Ok(dep);
             }
-            let Some((workspace, dep1)) = matching_crates.pop() else {
-                bail!("No matching crates found for dependency '{}'", name);
-            };
-            let mut dep_path = dep1.path.clone().unwrap();
-            if dep_path.is_absolute() {
-                dep.path = Some(dep_path);
-            } else {
-                dep_path = workspace.root_path.join(dep_path);
-                dep.path = Some(diff_path(manifest_root_path, &dep_path));
+            DependencySource::Nexus => {
+                let Some(package) = self.find_nexus_package(manifest_root_path, name, &dep)? else {
+                    return Ok(None);
+                };
+                if let Err(err) = check_version(manifest_root_path, name, &dep, &package) {
+                    if has_next {
+                        info!("{}, trying the next source", err);
+                        return Ok(None);
+                    }
+                    return Err(err);
+                }
+                dep.path = Some(diff_path(manifest_root_path, &package.root_path));
+                self.apply_mode(name, &mut dep, &package)?;
+                dep.nexus = None;
+                dep.workspace = None;
+                Ok(Some(dep))
+            }
+            DependencySource::Path => {
+                let Some(path) = &dep.path else {
+                    return Ok(None);
+                };
+                if !manifest_root_path.join(path).exists() {
+                    return Ok(None);
+                }
+                dep.nexus = None;
+                dep.workspace = None;
+                dep.git = None;
+                Ok(Some(dep))
+            }
+            DependencySource::Registry => {
+                if dep.version.is_none() {
+                    return Ok(None);
+                }
+                dep.nexus = None;
+                dep.workspace = None;
+                dep.path = None;
+                dep.git = None;
+                dep.branch = None;
+                dep.tag = None;
+                dep.rev = None;
+                Ok(Some(dep))
+            }
+            DependencySource::Git => {
+                if dep.git.is_none() {
+                    return Ok(None);
+                }
+                dep.nexus = None;
+                dep.workspace = None;
+                dep.path = None;
+                Ok(Some(dep))
             }
+        }
+    }
 
-            dep.nexus = None;
-            dep.workspace = None;
-            return Ok(dep);
+    /// Rewrite a path dependency on a local package according to the resolution mode
+    pub fn apply_mode(
+        &self,
+        name: &str,
+        dep: &mut DependencyModel,
+        package: &PackageModel,
+    ) -> Result<()> {
+        match self.mode {
+            ResolutionMode::Path => Ok(()),
+            ResolutionMode::Publish => self.publish_dependency(name, dep, package),
+            ResolutionMode::Git => self.git_dependency(name, dep, package),
         }
-        Ok(dep)
     }
+
+    /// Turn a dependency on a local package into a git dependency on the commit checked out
+    /// in its repository, or on the tag pointing at that commit
+    pub fn git_dependency(
+        &self,
+        name: &str,
+        dep: &mut DependencyModel,
+        package: &PackageModel,
+    ) -> Result<()> {
+        let source = GitSource::from_dir(&package.root_path).with_context(|| {
+            format!(
+                "Dependency '{}' cannot be resolved to git: package {} at {} is not in a git repository with a remote",
+                name,
+                package.name,
+                package.root_path.display()
+            )
+        })?;
+        dep.path = None;
+        dep.git = Some(source.url);
+        match source.tag {
+            Some(tag) => dep.tag = Some(tag),
+            None => dep.rev = Some(source.rev),
+        }
+        dep.package = Some(package.name.clone());
+        Ok(())
+    }
+
+    /// Turn a dependency on a local package into a registry dependency on its version.
+    ///
+    /// The path is dropped unless `keep_path` is set. Fails if the package cannot be published.
+    pub fn publish_dependency(
+        &self,
+        name: &str,
+        dep: &mut DependencyModel,
+        package: &PackageModel,
+    ) -> Result<()> {
+        if package
+            .publish
+            .as_ref()
+            .is_some_and(|publish| !publish.is_publishable())
+        {
+            bail!(MagnetError::UnresolvedDependency {
+                name: name.to_string(),
+                message: format!(
+                    "Dependency '{}' cannot be resolved for publishing: package {} at {} has `publish = false`",
+                    name,
+                    package.name,
+                    package.root_path.display()
+                ),
+            });
+        }
+        if package.version.is_empty() {
+            bail!(MagnetError::UnresolvedDependency {
+                name: name.to_string(),
+                message: format!(
+                    "Dependency '{}' cannot be resolved for publishing: package {} at {} has no version",
+                    name,
+                    package.name,
+                    package.root_path.display()
+                ),
+            });
+        }
+        dep.version = Some(package.version.clone());
+        if !self.keep_path {
+            dep.path = None;
+        }
+        Ok(())
+    }
+
+    /// Resolve `nexus = true` and `workspace = true` in every dependency table of a package,
+    /// including target-specific tables
     pub fn resolve_package_dependencies(&mut self, package: &mut PackageModel) -> Result<()> {
-        for (name, dep) in package.dependencies.clone() {
+        // Pick up manifests changed since the previous package was resolved
+        self.index()?;
+        let root_path = package.root_path.clone();
+        for kind in DependencyKind::ALL {
+            self.resolve_dependency_map(&root_path, package.dependency_map_mut(kind))?;
+            for target in package.target.values_mut() {
+                self.resolve_dependency_map(&root_path, target.dependency_map_mut(kind))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve all dependencies of a single dependency table in place
+    pub fn resolve_dependency_map(
+        &mut self,
+        manifest_root_path: &Path,
+        dependencies: &mut DependencyModelMap,
+    ) -> Result<()> {
+        for (name, dep) in dependencies.clone() {
             // Resolve the dependency
-            let resolved = self.resolve_dependency(&package.root_path, &name, &dep);
+            let resolved = self.resolve_dependency(manifest_root_path, &name, &dep);
             match resolved {
                 Ok(detailed) => {
                     // Update the package dependencies
-                    package.dependencies.insert(name.clone(), detailed);
+                    dependencies.insert(name.clone(), detailed);
                 }
-                Err(err) => {
-                    if dep.optional() {
-                        warn!("Error resolving dependency '{}': {}", name, err);
-                        warn!(
-                            "This could be you don't have sufficient permissions to access the workspace"
-                        );
-                        package.dependencies.remove(&name);
-                    }
-                    Err(err)?
+                Err(err) if dep.optional() && !self.strict => {
+                    warn!("Error resolving dependency '{}': {}", name, err);
+                    dependencies.remove(&name);
                 }
+                Err(err) => Err(err)?,
             }
         }
         Ok(())
     }
 }
+
+/// Make the relative path of a dependency declared in `from` relative to `to` instead
+fn rebase_dependency(dep: &DependencyModel, from: &Path, to: &Path) -> DependencyModel {
+    let mut dep = dep.clone();
+    if let Some(path) = &dep.path
+        && path.is_relative()
+    {
+        dep.path = Some(diff_path(to, &from.join(path)));
+    }
+    dep
+}
+
+/// Combine a `workspace = true` dependency with its shared definition the way Cargo does:
+/// the definition provides the source, the member may add features and make it optional
+fn inherit_dependency(
+    dep: &DependencyModel,
+    definition: &DependencyModel,
+    base_path: &Path,
+    manifest_root_path: &Path,
+) -> DependencyModel {
+    let mut inherited = rebase_dependency(definition, base_path, manifest_root_path);
+    if let Some(features) = &dep.features {
+        let all_features = inherited.features.get_or_insert_with(Vec::new);
+        for feature in features {
+            if !all_features.contains(feature) {
+                all_features.push(feature.clone());
+            }
+        }
+    }
+    inherited.optional = dep.optional.or(inherited.optional);
+    inherited.target = dep.target.clone().or(inherited.target);
+    inherited.custom.extend(dep.custom.clone());
+    inherited.package = dep.package.clone().or(inherited.package);
+    inherited.version_check = dep.version_check.or(inherited.version_check);
+    inherited.workspace = None;
+    inherited
+}
+
+/// Check that the local package found for a `nexus` dependency satisfies its `version`
+/// requirement, unless the dependency sets `version-check = false`
+fn check_version(
+    manifest_root_path: &Path,
+    name: &str,
+    dep: &DependencyModel,
+    package: &PackageModel,
+) -> Result<()> {
+    let Some(requirement) = &dep.version else {
+        return Ok(());
+    };
+    if !dep.version_check() {
+        return Ok(());
+    }
+    let requirement =
+        VersionReq::parse(requirement).map_err(|err| MagnetError::InvalidManifest {
+            path: manifest_root_path.to_path_buf(),
+            message: format!(
+                "invalid version requirement '{}' for dependency '{}': {}",
+                requirement, name, err
+            ),
+        })?;
+    let version = Version::parse(&package.version).map_err(|err| MagnetError::InvalidManifest {
+        path: package.source_path.clone(),
+        message: format!(
+            "invalid version '{}' of package '{}': {}",
+            package.version, package.name, err
+        ),
+    })?;
+    if !requirement.matches(&version) {
+        bail!(MagnetError::VersionMismatch {
+            name: name.to_string(),
+            message: format!(
+                "Dependency '{}' of {} requires version {}, but package '{}' found in the nexus at {} is version {}\nCheck out a matching version of the package, or set `version-check = false` on the dependency",
+                name,
+                manifest_root_path.display(),
+                requirement,
+                package.name,
+                package.root_path.display(),
+                version
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// One line per candidate package, with the workspace holding it
+fn describe_candidates(candidates: &[(&PackageModel, Option<&WorkspaceModel>)]) -> String {
+    candidates
+        .iter()
+        .map(|(package, workspace)| match workspace {
+            Some(workspace) => format!(
+                "  - {} (workspace '{}')",
+                package.root_path.display(),
+                workspace.name
+            ),
+            None => format!("  - {} (not in a workspace)", package.root_path.display()),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
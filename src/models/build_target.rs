@@ -0,0 +1,118 @@
+//! Domain model for Cargo build targets (`[lib]`, `[[bin]]`, `[[test]]`, `[[bench]]`,
+//! `[[example]]`) and the `[features]` table.
+
+use crate::MagnetError;
+use crate::models::{DependencyKind, PackageModel};
+use eyre::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Map of feature name to the features and dependencies it enables
+pub type FeatureMap = HashMap<String, Vec<String>>;
+
+/// Configuration of a single Cargo build target
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BuildTargetModel {
+    /// Name of the target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Path to the target's source file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Whether the target is tested by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test: Option<bool>,
+    /// Whether documentation examples are tested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doctest: Option<bool>,
+    /// Whether the target is benchmarked by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bench: Option<bool>,
+    /// Whether the target is documented by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<bool>,
+    /// Whether the library is a procedural macro
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proc_macro: Option<bool>,
+    /// Whether to use the libtest harness
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub harness: Option<bool>,
+    /// Edition of the target, if different from the package
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edition: Option<String>,
+    /// Crate types to generate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crate_type: Option<Vec<String>>,
+    /// Features required to build the target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_features: Option<Vec<String>>,
+    /// Custom target settings
+    #[serde(flatten)]
+    pub custom: HashMap<String, toml::Value>,
+}
+
+/// Check that every feature entry naming a dependency (`dep:foo`, `foo/bar`, `foo?/bar`)
+/// refers to a dependency declared by the package, and bare entries name a feature or a
+/// dependency. Like Cargo, `dep:foo`, `foo?/bar` and bare `foo` require `foo` to be optional.
+pub fn validate_features(package: &PackageModel) -> Result<()> {
+    // Name of each dependency and whether it is optional in any of its tables
+    let mut dependencies: HashMap<&str, bool> = HashMap::new();
+    // Dev-dependencies cannot be activated by features
+    for kind in [DependencyKind::Normal, DependencyKind::Build] {
+        let maps = std::iter::once(package.dependency_map(kind)).chain(
+            package
+                .target
+                .values()
+                .map(|target| target.dependency_map(kind)),
+        );
+        for (name, dep) in maps.flatten() {
+            *dependencies.entry(name.as_str()).or_default() |= dep.optional.unwrap_or(false);
+        }
+    }
+
+    let mut errors = Vec::new();
+    for (feature, entries) in &package.features {
+        for entry in entries {
+            let (dep, needs_optional) = if let Some(dep) = entry.strip_prefix("dep:") {
+                (dep, true)
+            } else if let Some((dep, _)) = entry.split_once('/') {
+                match dep.strip_suffix('?') {
+                    Some(dep) => (dep, true),
+                    None => (dep, false),
+                }
+            } else if package.features.contains_key(entry) {
+                continue;
+            } else {
+                (entry.as_str(), true)
+            };
+            match dependencies.get(dep) {
+                Some(false) if needs_optional => errors.push(format!(
+                    "feature '{}' enables '{}', but '{}' is not an optional dependency",
+                    feature, entry, dep
+                )),
+                Some(_) => {}
+                None if dep == entry => errors.push(format!(
+                    "feature '{}' enables '{}', which is neither a feature nor a dependency",
+                    feature, entry
+                )),
+                None => errors.push(format!(
+                    "feature '{}' enables '{}', but '{}' is not a dependency",
+                    feature, entry, dep
+                )),
+            }
+        }
+    }
+    if !errors.is_empty() {
+        errors.sort();
+        bail!(MagnetError::InvalidManifest {
+            path: package.source_path.clone(),
+            message: format!(
+                "invalid features in package '{}':\n  {}",
+                package.name,
+                errors.join("\n  ")
+            ),
+        });
+    }
+    Ok(())
+}
@@ -14,7 +14,13 @@ pub struct DependencyModel {
     pub path: Option<PathBuf>,
     /// Automatically resolve path to this dependency if found in any workspace
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub nexus: Option<bool>,
+    pub nexus: Option<NexusDependency>,
+    /// Whether the package found in the nexus must satisfy `version` (defaults to true)
+    #[serde(rename = "version-check", skip_serializing_if = "Option::is_none")]
+    pub version_check: Option<bool>,
+    /// Sources tried in order to resolve the dependency, the first one that applies is used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<Vec<DependencySource>>,
     /// Git repository URL
     #[serde(skip_serializing_if = "Option::is_none")]
     pub git: Option<String>,
@@ -49,16 +55,79 @@ pub struct DependencyModel {
     /// Artifact to use
     #[serde(skip_serializing_if = "Option::is_none")]
     pub artifact: Option<String>,
-    /// Target to use
+    /// Target to use. For artifact dependencies this is passed through to Cargo,
+    /// otherwise the dependency is moved into the matching `[target.<target>]` table
     #[serde(skip_serializing_if = "Option::is_none")]
     pub target: Option<String>,
     /// Custom dependency metadata
     #[serde(flatten)]
     pub custom: HashMap<String, toml::Value>,
 }
+/// Value of the `nexus` key of a dependency
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum NexusDependency {
+    /// `nexus = true`: the package is looked up in every workspace of the nexus
+    Enabled(bool),
+    /// `nexus = "<workspace>"`: the package is taken from the named member workspace
+    Workspace(String),
+}
+
+impl Display for NexusDependency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NexusDependency::Enabled(enabled) => write!(f, "{}", enabled),
+            NexusDependency::Workspace(workspace) => write!(f, "{:?}", workspace),
+        }
+    }
+}
+
+/// A place a dependency can be resolved from, as listed in its `fallback` chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencySource {
+    /// A package with the same name found in the nexus
+    Nexus,
+    /// The shared definition in `[workspace.dependencies]` or the nexus `[dependencies]`
+    Workspace,
+    /// The `path` of the dependency
+    Path,
+    /// The `version` of the dependency, from the registry
+    Registry,
+    /// The `git` repository of the dependency
+    Git,
+}
+
+impl Display for DependencySource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DependencySource::Nexus => "nexus",
+            DependencySource::Workspace => "workspace",
+            DependencySource::Path => "path",
+            DependencySource::Registry => "registry",
+            DependencySource::Git => "git",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 impl DependencyModel {
     pub fn nexus(&self) -> bool {
-        self.nexus.unwrap_or(false)
+        match &self.nexus {
+            Some(NexusDependency::Enabled(enabled)) => *enabled,
+            Some(NexusDependency::Workspace(_)) => true,
+            None => false,
+        }
+    }
+    /// Member workspace named by `nexus = "<workspace>"`
+    pub fn nexus_workspace(&self) -> Option<&str> {
+        match &self.nexus {
+            Some(NexusDependency::Workspace(workspace)) => Some(workspace),
+            _ => None,
+        }
+    }
+    pub fn version_check(&self) -> bool {
+        self.version_check.unwrap_or(true)
     }
     pub fn workspace(&self) -> bool {
         self.workspace.unwrap_or(false)
@@ -84,6 +153,13 @@ impl Display for DependencyModel {
         if let Some(nexus) = &self.nexus {
             write!(f, "nexus = {}, ", nexus)?;
         }
+        if let Some(version_check) = &self.version_check {
+            write!(f, "version-check = {}, ", version_check)?;
+        }
+        if let Some(fallback) = &self.fallback {
+            let fallback: Vec<String> = fallback.iter().map(ToString::to_string).collect();
+            write!(f, "fallback = {:?}, ", fallback)?;
+        }
         if let Some(git) = &self.git {
             write!(f, "git = {:?}, ", git)?;
         }
@@ -133,3 +209,38 @@ impl Display for DependencyModel {
 }
 
 pub type DependencyModelMap = HashMap<String, DependencyModel>;
+
+/// Kind of a dependency table, matching Cargo's dependency sections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DependencyKind {
+    /// `[dependencies]`
+    Normal,
+    /// `[dev-dependencies]`
+    Dev,
+    /// `[build-dependencies]`
+    Build,
+}
+
+impl DependencyKind {
+    /// All dependency kinds, in the order Cargo lists them
+    pub const ALL: [DependencyKind; 3] = [
+        DependencyKind::Normal,
+        DependencyKind::Dev,
+        DependencyKind::Build,
+    ];
+
+    /// Name of the TOML table holding dependencies of this kind
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            DependencyKind::Normal => "dependencies",
+            DependencyKind::Dev => "dev-dependencies",
+            DependencyKind::Build => "build-dependencies",
+        }
+    }
+}
+
+impl Display for DependencyKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.table_name())
+    }
+}
@@ -0,0 +1,43 @@
+//! Domain model for `[lints]`, `[workspace.lints]` and `[nexus.lints]` tables.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Map of tool (`rust`, `clippy`, `rustdoc`) to the lints configured for it
+pub type LintsMap = HashMap<String, LintToolMap>;
+
+/// Map of lint name to its configuration
+pub type LintToolMap = HashMap<String, LintConfig>;
+
+/// Configuration of a single lint: `"warn"` or `{ level = "warn", priority = -1 }`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum LintConfig {
+    Level(String),
+    Detailed(DetailedLintConfig),
+}
+
+/// Detailed lint configuration
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DetailedLintConfig {
+    /// Lint level: allow, warn, deny or forbid
+    pub level: String,
+    /// Priority of the lint, lower priorities are applied first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i64>,
+    /// Other lint settings, e.g. `check-cfg`
+    #[serde(flatten)]
+    pub custom: HashMap<String, toml::Value>,
+}
+
+/// Merge two lint tables; lints in `overrides` win over the same lints in `base`
+pub fn merge_lints(base: &LintsMap, overrides: &LintsMap) -> LintsMap {
+    let mut merged = base.clone();
+    for (tool, lints) in overrides {
+        let merged_lints = merged.entry(tool.clone()).or_default();
+        for (lint, config) in lints {
+            merged_lints.insert(lint.clone(), config.clone());
+        }
+    }
+    merged
+}
@@ -1,19 +1,28 @@
-use eyre::{Result, bail, ensure};
+use eyre::{Result, bail};
 use std::path::{Path, PathBuf};
 
+mod build_target;
 #[path = "crate.rs"]
 mod crate_;
 mod dependency;
+mod lints;
 mod nexus;
 mod package;
+mod profile;
+mod target;
 mod workspace;
 mod patch;
 
+use crate::MagnetError;
 use crate::configs::ManifestConfig;
+pub use build_target::*;
 pub use crate_::*;
 pub use dependency::*;
+pub use lints::*;
 pub use nexus::*;
 pub use package::*;
+pub use profile::*;
+pub use target::*;
 pub use workspace::*;
 pub use patch::*;
 
@@ -22,8 +31,13 @@ pub use patch::*;
 /// - a WorkspaceModel
 /// - a PackageModel
 ///
-/// There are root manifest case in cargo, but we don't support it yet.
+/// A manifest may combine sections, in which case the root plays every role:
+/// - `[workspace]` with `[package]` is a workspace whose root is also a member package
+/// - `[nexus]` with `[workspace]` is a nexus whose root is also a member workspace
+///
+/// The variant is the outermost role; the inner roles are listed as members.
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum ManifestModel {
     Nexus(NexusModel),
     Workspace(WorkspaceModel),
@@ -38,19 +52,21 @@ impl ManifestModel {
         } else if root_path.join("Cargo.toml").exists() {
             root_path.join("Cargo.toml")
         } else {
-            bail!(
-                "No Magnet.toml or Cargo.toml file found in the directory: {}",
-                root_path.display()
-            );
+            bail!(MagnetError::ManifestNotFound { path: root_path });
         };
         let config = ManifestConfig::from_file(&config_path)?;
-        let section_count = (config.nexus.is_some() as u8)
-            + (config.workspace.is_some() as u8)
-            + (config.package.is_some() as u8);
-        ensure!(
-            section_count == 1,
-            "Only one section of [nexus], [workspace], [package] is allowed in the manifest file"
-        );
+        if config.nexus.is_none() && config.workspace.is_none() && config.package.is_none() {
+            bail!(MagnetError::InvalidManifest {
+                path: config_path,
+                message: "no [nexus], [workspace] or [package] section found".to_string(),
+            });
+        }
+        if config.nexus.is_some() && config.package.is_some() && config.workspace.is_none() {
+            bail!(MagnetError::InvalidManifest {
+                path: config_path,
+                message: "a nexus can only contain a root package through a root workspace, add a [workspace] section".to_string(),
+            });
+        }
 
         if let Some(_nexus) = config.nexus {
             NexusModel::from_dir(&root_path).map(ManifestModel::Nexus)
@@ -94,7 +110,7 @@ impl ManifestModel {
     }
     pub fn list_packages(&self) -> Result<Vec<PackageModel>> {
         match self {
-            ManifestModel::Nexus(nexus) => nexus.list_packages(),
+            ManifestModel::Nexus(nexus) => nexus.list_all_packages(),
             ManifestModel::Workspace(workspace) => workspace.list_packages(),
             ManifestModel::Package(package) => Ok(vec![package.clone()]),
         }
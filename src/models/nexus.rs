@@ -1,12 +1,16 @@
 //! Domain model for a Nexus, which represents a collection of workspaces.
 
+use crate::MagnetError;
 use crate::configs::ManifestConfig;
-use crate::models::{PackageModel, PatchMap, WorkspaceModel};
-use crate::utils::glob_relative;
+use crate::models::{
+    DependencyModelMap, LintsMap, ManifestModel, PackageModel, PatchMap, ProfileMap, WorkspaceModel,
+};
+use crate::utils::find_members;
 use eyre::ContextCompat;
-use eyre::Result;
+use eyre::{Result, bail};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use tracing::warn;
 
 /// A nexus model representing a collection of workspaces
 #[derive(Debug, Clone)]
@@ -17,11 +21,26 @@ pub struct NexusModel {
     pub version: Option<String>,
     /// Description of the nexus
     pub description: Option<String>,
+    /// Whether the nexus root is also a workspace (`[nexus]` and `[workspace]` in one
+    /// manifest); the root workspace is then the first member
+    pub root_workspace: bool,
     /// Workspaces included in this nexus (patterns)
     pub members: Vec<String>,
     /// Workspaces excluded from this nexus (patterns)
     pub exclude: Vec<String>,
+    /// Whether members ignored by git are skipped
+    pub respect_gitignore: bool,
+    /// Member workspaces (names or paths) preferred, in order, when a package name is
+    /// found in several of them
+    pub priority: Vec<String>,
+    /// Dependencies shared by every workspace in the nexus (top-level `[dependencies]`),
+    /// with paths relative to the nexus root
+    pub dependencies: DependencyModelMap,
     pub patch: PatchMap,
+    /// Default lints for every workspace in the nexus
+    pub lints: LintsMap,
+    /// Default build profiles for every workspace in the nexus (`[profile.*]`)
+    pub profile: ProfileMap,
     /// Custom nexus metadata
     pub custom: HashMap<String, toml::Value>,
     pub root_path: PathBuf,
@@ -50,9 +69,19 @@ impl NexusModel {
             name,
             version: config1.version,
             description: config1.description,
+            root_workspace: config.workspace.is_some(),
             members: config1.members,
             exclude: config1.exclude,
+            respect_gitignore: config1.respect_gitignore,
+            priority: config1.priority,
+            lints: config1.lints,
+            profile: config.profile,
             custom: config1.custom.clone(),
+            dependencies: config
+                .dependencies
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
             patch: config.patch,
             root_path,
             source_path,
@@ -60,34 +89,183 @@ impl NexusModel {
 
         Ok(model)
     }
+    /// Whether `reference`, from a `nexus = "<workspace>"` dependency or the `priority` list,
+    /// names `workspace`, either by name or by path relative to the nexus root
+    pub fn refers_to(&self, reference: &str, workspace: &WorkspaceModel) -> bool {
+        workspace.name == reference
+            || self
+                .root_path
+                .join(reference)
+                .canonicalize()
+                .is_ok_and(|path| path == workspace.root_path)
+    }
     pub fn list_members(&self) -> Result<Vec<PathBuf>> {
         let mut valid_members = Vec::new();
-        for pattern in &self.members {
-            valid_members.extend(glob_relative(&self.source_path, pattern, true)?);
+        if self.root_workspace {
+            valid_members.push(self.root_path.clone());
+        }
+        for path in find_members(
+            &self.root_path,
+            &self.members,
+            &self.exclude,
+            self.respect_gitignore,
+        )? {
+            if !valid_members.contains(&path) {
+                valid_members.push(path);
+            }
         }
         Ok(valid_members)
     }
+    /// Members listed by path (not by pattern) whose directory does not exist, such as
+    /// repositories that are not checked out
+    pub fn missing_members(&self) -> Vec<PathBuf> {
+        self.members
+            .iter()
+            .filter(|member| !member.contains(['*', '?', '[']))
+            .map(|member| self.root_path.join(member))
+            .filter(|path| !path.exists())
+            .collect()
+    }
+    /// Members of the nexus by kind, with nested nexuses discovered recursively.
+    ///
+    /// Fails when a nexus is (indirectly) a member of itself, or when the same directory is a
+    /// member of more than one nexus.
+    pub fn members(&self) -> Result<NexusMembers> {
+        let mut walker = MemberWalker {
+            stack: vec![self.root_path.clone()],
+            owners: HashMap::new(),
+        };
+        walker.walk(self)
+    }
+
+    /// All workspaces of the nexus, including the ones of nested nexuses
     pub fn list_workspaces(&self) -> Result<Vec<WorkspaceModel>> {
-        let all_members: Vec<PathBuf> = self.list_members()?;
-        let mut workspaces = Vec::new();
-        for member in all_members.iter() {
-            let Ok(workspace) = WorkspaceModel::from_dir(member) else {
-                continue;
-            };
-            workspaces.push(workspace);
-        }
-        Ok(workspaces)
+        Ok(self.members()?.all_workspaces())
     }
 
+    /// Packages that are direct members of the nexus, outside of any member workspace
     pub fn list_packages(&self) -> Result<Vec<PackageModel>> {
-        let all_members: Vec<PathBuf> = self.list_members()?;
-        let mut packages = Vec::new();
-        for member in all_members {
-            let Ok(package) = PackageModel::from_dir(&member) else {
-                continue;
-            };
-            packages.push(package);
+        Ok(self.members()?.packages)
+    }
+
+    /// All packages of the nexus: its direct members, the members of its workspaces and the
+    /// packages of nested nexuses
+    pub fn list_all_packages(&self) -> Result<Vec<PackageModel>> {
+        self.members()?.all_packages()
+    }
+}
+
+/// Members of a nexus, with nested nexuses expanded
+#[derive(Debug, Clone, Default)]
+pub struct NexusMembers {
+    /// Nested nexuses, with their own members
+    pub nexuses: Vec<(NexusModel, NexusMembers)>,
+    pub workspaces: Vec<WorkspaceModel>,
+    pub packages: Vec<PackageModel>,
+    /// Members listed by path that are not checked out
+    pub missing: Vec<PathBuf>,
+}
+
+impl NexusMembers {
+    /// Workspaces of this nexus and of every nested nexus
+    pub fn all_workspaces(&self) -> Vec<WorkspaceModel> {
+        let mut workspaces = self.workspaces.clone();
+        for (_, members) in &self.nexuses {
+            workspaces.extend(members.all_workspaces());
+        }
+        workspaces
+    }
+
+    /// Missing members of this nexus and of every nested nexus
+    pub fn all_missing(&self) -> Vec<PathBuf> {
+        let mut missing = self.missing.clone();
+        for (_, members) in &self.nexuses {
+            missing.extend(members.all_missing());
+        }
+        missing
+    }
+
+    /// Packages of this nexus, of its workspaces and of every nested nexus
+    pub fn all_packages(&self) -> Result<Vec<PackageModel>> {
+        let mut packages = self.packages.clone();
+        for workspace in &self.workspaces {
+            packages.extend(workspace.list_packages()?);
+        }
+        for (_, members) in &self.nexuses {
+            packages.extend(members.all_packages()?);
         }
         Ok(packages)
     }
 }
+
+/// Walks nested nexuses, detecting cycles and directories claimed by several nexuses
+struct MemberWalker {
+    /// Root paths of the nexuses being walked, outermost first
+    stack: Vec<PathBuf>,
+    /// Nexus that listed each member found so far
+    owners: HashMap<PathBuf, PathBuf>,
+}
+
+impl MemberWalker {
+    fn walk(&mut self, nexus: &NexusModel) -> Result<NexusMembers> {
+        let mut members = NexusMembers {
+            missing: nexus.missing_members(),
+            ..NexusMembers::default()
+        };
+        for member in nexus.list_members()? {
+            let member = canonical(&member);
+            // The root workspace of a nexus shares its manifest
+            if member == nexus.root_path {
+                members.workspaces.push(WorkspaceModel::from_dir(&member)?);
+                continue;
+            }
+            if self.stack.contains(&member) {
+                let cycle: Vec<String> = self
+                    .stack
+                    .iter()
+                    .chain([&member])
+                    .map(|path| path.display().to_string())
+                    .collect();
+                bail!(MagnetError::InvalidManifest {
+                    path: nexus.source_path.clone(),
+                    message: format!("Nexus cycle detected: {}", cycle.join(" -> ")),
+                });
+            }
+            if let Some(owner) = self.owners.get(&member) {
+                bail!(MagnetError::InvalidManifest {
+                    path: nexus.source_path.clone(),
+                    message: format!(
+                        "{} is a member of both nexus {} and nexus {}",
+                        member.display(),
+                        owner.display(),
+                        nexus.root_path.display()
+                    ),
+                });
+            }
+            self.owners.insert(member.clone(), nexus.root_path.clone());
+
+            match ManifestModel::from_dir(&member) {
+                Ok(ManifestModel::Nexus(nested)) => {
+                    self.stack.push(member);
+                    let nested_members = self.walk(&nested)?;
+                    self.stack.pop();
+                    members.nexuses.push((nested, nested_members));
+                }
+                Ok(ManifestModel::Workspace(workspace)) => members.workspaces.push(workspace),
+                Ok(ManifestModel::Package(package)) => members.packages.push(package),
+                Err(err) => warn!(
+                    "Skipping member {} of nexus {}: {}",
+                    member.display(),
+                    nexus.root_path.display(),
+                    err
+                ),
+            }
+        }
+        Ok(members)
+    }
+}
+
+/// Member paths may contain `..`, resolve them so the same directory is always the same path
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
@@ -1,7 +1,11 @@
-use crate::configs::ManifestConfig;
-use crate::models::{DependencyModelMap, PatchMap};
+use crate::MagnetError;
+use crate::configs::{InheritableField, ManifestConfig, PublishConfig};
+use crate::models::{
+    BuildTargetModel, DependencyKind, DependencyModelMap, FeatureMap, LintsMap, PatchMap,
+    ProfileMap, TargetDependencyModelMap, WorkspacePackageModel,
+};
 use eyre::ContextCompat;
-use eyre::Result;
+use eyre::{Result, bail};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
@@ -25,9 +29,44 @@ pub struct PackageModel {
     pub documentation: Option<String>,
     /// Package license
     pub license: Option<String>,
+    /// Path to a custom license file
+    pub license_file: Option<String>,
+    /// Minimum supported Rust version
+    pub rust_version: Option<String>,
+    /// Keywords for the registry
+    pub keywords: Option<Vec<String>>,
+    /// Categories for the registry
+    pub categories: Option<Vec<String>>,
+    /// Whether and where the package may be published
+    pub publish: Option<PublishConfig>,
+    /// Names of the fields inherited from `[workspace.package]`; their values above are
+    /// already resolved
+    pub inherited: Vec<String>,
     /// Custom package metadata
     pub custom: HashMap<String, toml::Value>,
     pub dependencies: DependencyModelMap,
+    /// Development dependencies (tests, examples and benchmarks)
+    pub dev_dependencies: DependencyModelMap,
+    /// Build script dependencies
+    pub build_dependencies: DependencyModelMap,
+    /// Target-specific dependency tables
+    pub target: TargetDependencyModelMap,
+    /// Features of the package
+    pub features: FeatureMap,
+    /// Library target
+    pub lib: Option<BuildTargetModel>,
+    /// Binary targets
+    pub bin: Vec<BuildTargetModel>,
+    /// Integration test targets
+    pub test: Vec<BuildTargetModel>,
+    /// Benchmark targets
+    pub bench: Vec<BuildTargetModel>,
+    /// Example targets
+    pub example: Vec<BuildTargetModel>,
+    /// Lints of the package, or `workspace = true` to use the workspace lints
+    pub lints: Option<InheritableField<LintsMap>>,
+    /// Build profiles declared by the package; Cargo ignores them outside the workspace root
+    pub profile: ProfileMap,
     /// Patch section for overriding dependencies
     pub patch: PatchMap,
     pub root_path: PathBuf,
@@ -47,10 +86,7 @@ impl PackageModel {
         } else if root_path.join("Cargo.toml").exists() {
             root_path.join("Cargo.toml")
         } else {
-            eyre::bail!(
-                "Root path must point to Cargo.toml or Magnet.toml: {}",
-                root_path.display()
-            )
+            bail!(MagnetError::ManifestNotFound { path: root_path })
         };
         let config = ManifestConfig::from_file(&config_path)?;
 
@@ -58,28 +94,241 @@ impl PackageModel {
             .package
             .clone()
             .with_context(|| format!("No package found in {}", root_path.display()))?;
+        // Resolve fields inherited from [workspace.package]
+        let mut inheritance = Inheritance {
+            root_path: &root_path,
+            workspace_package: None,
+            inherited: Vec::new(),
+        };
+        let version = inheritance.resolve("version", &package.version, |ws| ws.version.clone())?;
+        let edition = inheritance
+            .resolve_opt("edition", &package.edition, |ws| ws.edition.clone())?
+            .unwrap_or("2024".to_string());
+        let description = inheritance.resolve("description", &package.description, |ws| {
+            ws.description.clone()
+        })?;
+        let authors = inheritance.resolve("authors", &package.authors, |ws| ws.authors.clone())?;
+        let homepage =
+            inheritance.resolve_opt("homepage", &package.homepage, |ws| ws.homepage.clone())?;
+        let repository = inheritance.resolve_opt("repository", &package.repository, |ws| {
+            ws.repository.clone()
+        })?;
+        let documentation =
+            inheritance.resolve_opt("documentation", &package.documentation, |ws| {
+                ws.documentation.clone()
+            })?;
+        let license =
+            inheritance.resolve_opt("license", &package.license, |ws| ws.license.clone())?;
+        let license_file =
+            inheritance.resolve_opt("license-file", &package.license_file, |ws| {
+                ws.license_file.clone()
+            })?;
+        let rust_version =
+            inheritance.resolve_opt("rust-version", &package.rust_version, |ws| {
+                ws.rust_version.clone()
+            })?;
+        let keywords =
+            inheritance.resolve_opt("keywords", &package.keywords, |ws| ws.keywords.clone())?;
+        let categories = inheritance.resolve_opt("categories", &package.categories, |ws| {
+            ws.categories.clone()
+        })?;
+        let publish =
+            inheritance.resolve_opt("publish", &package.publish, |ws| ws.publish.clone())?;
+        let inherited = inheritance.inherited;
+
         // Create a new PackageModel instance
-        let model = PackageModel {
+        let mut model = PackageModel {
             name: package.name,
-            version: package.version,
-            edition: config.get_edition().unwrap_or("2024".to_string()),
-            description: package.description,
-            authors: package.authors,
-            homepage: package.homepage,
-            repository: package.repository,
-            documentation: package.documentation,
-            license: package.license,
+            version,
+            edition,
+            description,
+            authors,
+            homepage,
+            repository,
+            documentation,
+            license,
+            license_file,
+            rust_version,
+            keywords,
+            categories,
+            publish,
+            inherited,
             custom: package.custom,
-            dependencies: config.dependencies
+            dependencies: config
+                .dependencies
+                .clone()
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+            dev_dependencies: config
+                .dev_dependencies
                 .clone()
                 .into_iter()
                 .map(|(k, v)| (k, v.into()))
                 .collect(),
+            build_dependencies: config
+                .build_dependencies
+                .clone()
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+            target: config
+                .target
+                .clone()
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+            features: config.features,
+            lib: config.lib,
+            bin: config.bin,
+            test: config.test,
+            bench: config.bench,
+            example: config.example,
+            lints: config.lints,
+            // A root package shares its manifest with the workspace, which owns the profiles
+            profile: match config.workspace {
+                Some(_) => ProfileMap::new(),
+                None => config.profile,
+            },
             patch: config.patch,
             root_path: root_path.to_path_buf(),
             source_path: config_path,
         };
+        model.lift_target_dependencies();
 
         Ok(model)
     }
+
+    /// Whether a field (by its Cargo name, e.g. `rust-version`) is inherited from the workspace
+    pub fn is_inherited(&self, field: &str) -> bool {
+        self.inherited.iter().any(|name| name == field)
+    }
+
+    /// Move dependencies declared with `target = "..."` into their `[target.<target>]` table,
+    /// which is the only form Cargo understands. Artifact dependencies keep their `target` key.
+    fn lift_target_dependencies(&mut self) {
+        for kind in DependencyKind::ALL {
+            let lifted: Vec<String> = self
+                .dependency_map(kind)
+                .iter()
+                .filter(|(_, dep)| dep.target.is_some() && dep.artifact.is_none())
+                .map(|(name, _)| name.clone())
+                .collect();
+            for name in lifted {
+                let mut dep = self.dependency_map_mut(kind).remove(&name).unwrap();
+                let target = dep.target.take().unwrap();
+                self.target
+                    .entry(target)
+                    .or_default()
+                    .dependency_map_mut(kind)
+                    .insert(name, dep);
+            }
+        }
+    }
+
+    /// Get the dependency table of the given kind
+    pub fn dependency_map(&self, kind: DependencyKind) -> &DependencyModelMap {
+        match kind {
+            DependencyKind::Normal => &self.dependencies,
+            DependencyKind::Dev => &self.dev_dependencies,
+            DependencyKind::Build => &self.build_dependencies,
+        }
+    }
+
+    /// Get the mutable dependency table of the given kind
+    pub fn dependency_map_mut(&mut self, kind: DependencyKind) -> &mut DependencyModelMap {
+        match kind {
+            DependencyKind::Normal => &mut self.dependencies,
+            DependencyKind::Dev => &mut self.dev_dependencies,
+            DependencyKind::Build => &mut self.build_dependencies,
+        }
+    }
+}
+
+/// Resolves package fields marked `workspace = true`, locating `[workspace.package]` on demand
+struct Inheritance<'a> {
+    root_path: &'a Path,
+    workspace_package: Option<WorkspacePackageModel>,
+    /// Names of the fields that were inherited
+    inherited: Vec<String>,
+}
+
+impl Inheritance<'_> {
+    fn resolve<T: Clone>(
+        &mut self,
+        name: &str,
+        field: &InheritableField<T>,
+        get: impl Fn(&WorkspacePackageModel) -> Option<T>,
+    ) -> Result<T> {
+        match field {
+            InheritableField::Value(value) => Ok(value.clone()),
+            InheritableField::Inherited(inherit) => {
+                if !inherit.workspace {
+                    bail!(
+                        "`{}.workspace = false` is not supported in {}",
+                        name,
+                        self.root_path.display()
+                    );
+                }
+                let root_path = self.root_path;
+                let value = get(self.workspace_package()?).with_context(|| {
+                    format!(
+                        "`{}` of package {} is inherited, but [workspace.package] does not set it",
+                        name,
+                        root_path.display()
+                    )
+                })?;
+                self.inherited.push(name.to_string());
+                Ok(value)
+            }
+        }
+    }
+
+    fn resolve_opt<T: Clone>(
+        &mut self,
+        name: &str,
+        field: &Option<InheritableField<T>>,
+        get: impl Fn(&WorkspacePackageModel) -> Option<T>,
+    ) -> Result<Option<T>> {
+        field
+            .as_ref()
+            .map(|field| self.resolve(name, field, get))
+            .transpose()
+    }
+
+    fn workspace_package(&mut self) -> Result<&WorkspacePackageModel> {
+        if self.workspace_package.is_none() {
+            self.workspace_package = Some(find_workspace_package(self.root_path)?);
+        }
+        Ok(self.workspace_package.as_ref().unwrap())
+    }
+}
+
+/// Find the `[workspace.package]` table of the closest workspace containing `root_path`
+fn find_workspace_package(root_path: &Path) -> Result<WorkspacePackageModel> {
+    for dir in root_path.ancestors() {
+        let config_path = if dir.join("Magnet.toml").exists() {
+            dir.join("Magnet.toml")
+        } else if dir.join("Cargo.toml").exists() {
+            dir.join("Cargo.toml")
+        } else {
+            continue;
+        };
+        let Ok(config) = ManifestConfig::from_file(&config_path) else {
+            continue;
+        };
+        if let Some(workspace) = config.workspace {
+            return workspace.package.with_context(|| {
+                format!(
+                    "No [workspace.package] found in {}, but package {} inherits from it",
+                    config_path.display(),
+                    root_path.display()
+                )
+            });
+        }
+    }
+    bail!(
+        "Package {} inherits fields from the workspace, but no workspace was found",
+        root_path.display()
+    )
 }
@@ -0,0 +1,34 @@
+//! Domain model for `[profile.*]` tables.
+
+use std::collections::HashMap;
+
+/// Map of profile name (`release`, `bench`, custom profiles) to its settings.
+///
+/// Settings are kept as raw TOML so every Cargo profile key, including
+/// `[profile.<name>.package.<spec>]` and `build-override`, is passed through.
+pub type ProfileMap = HashMap<String, toml::Table>;
+
+/// Merge two profile tables; settings in `overrides` win over the same settings in `base`.
+///
+/// Nested tables such as `package.<spec>` are merged key by key as well.
+pub fn merge_profiles(base: &ProfileMap, overrides: &ProfileMap) -> ProfileMap {
+    let mut merged = base.clone();
+    for (name, profile) in overrides {
+        let merged_profile = merged.entry(name.clone()).or_default();
+        merge_table(merged_profile, profile);
+    }
+    merged
+}
+
+fn merge_table(base: &mut toml::Table, overrides: &toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => {
+                merge_table(base, overrides)
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! Domain model for target-specific dependency tables.
+
+use crate::models::{DependencyKind, DependencyModelMap};
+use std::collections::HashMap;
+
+/// Map of target (a triple or `cfg(...)` expression) to its dependency tables
+pub type TargetDependencyModelMap = HashMap<String, TargetDependencyModel>;
+
+/// Dependency tables of a `[target.'cfg(...)']` section
+#[derive(Debug, Clone, Default)]
+pub struct TargetDependencyModel {
+    /// Dependencies only used on this target
+    pub dependencies: DependencyModelMap,
+    /// Development dependencies only used on this target
+    pub dev_dependencies: DependencyModelMap,
+    /// Build dependencies only used on this target
+    pub build_dependencies: DependencyModelMap,
+}
+
+impl TargetDependencyModel {
+    /// Get the dependency table of the given kind
+    pub fn dependency_map(&self, kind: DependencyKind) -> &DependencyModelMap {
+        match kind {
+            DependencyKind::Normal => &self.dependencies,
+            DependencyKind::Dev => &self.dev_dependencies,
+            DependencyKind::Build => &self.build_dependencies,
+        }
+    }
+
+    /// Get the mutable dependency table of the given kind
+    pub fn dependency_map_mut(&mut self, kind: DependencyKind) -> &mut DependencyModelMap {
+        match kind {
+            DependencyKind::Normal => &mut self.dependencies,
+            DependencyKind::Dev => &mut self.dev_dependencies,
+            DependencyKind::Build => &mut self.build_dependencies,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dependencies.is_empty()
+            && self.dev_dependencies.is_empty()
+            && self.build_dependencies.is_empty()
+    }
+}
@@ -1,12 +1,51 @@
 //! Domain model for a Workspace, which is a collection of packages.
 
-use crate::configs::ManifestConfig;
-use crate::models::{DependencyModel, DependencyModelMap, PackageModel, PatchMap};
-use crate::utils::glob_relative;
-use eyre::{bail, ContextCompat, Result};
+use crate::MagnetError;
+use crate::configs::{ManifestConfig, PublishConfig};
+use crate::models::{
+    DependencyModel, DependencyModelMap, LintsMap, PackageModel, PatchMap, ProfileMap,
+};
+use crate::utils::find_members;
+use eyre::{ContextCompat, Result, bail};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+/// Package fields shared by workspace members through `[workspace.package]`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WorkspacePackageModel {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authors: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rust_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish: Option<PublishConfig>,
+    /// Other inheritable fields, passed through as-is
+    #[serde(flatten)]
+    pub custom: HashMap<String, toml::Value>,
+}
+
 /// A workspace model representing a collection of packages
 #[derive(Debug, Clone)]
 pub struct WorkspaceModel {
@@ -18,9 +57,19 @@ pub struct WorkspaceModel {
     pub members: Vec<String>,
     /// Excluded workspace members (glob patterns)
     pub exclude: Vec<String>,
+    /// Whether members ignored by git are skipped
+    pub respect_gitignore: bool,
     /// Cargo resolver version (1 or 2)
     pub resolver: Option<String>,
-    
+    /// Whether the workspace root is also a package (`[workspace]` and `[package]` in one
+    /// manifest); the root package is then the first member
+    pub root_package: bool,
+    /// Package fields inherited by members (`[workspace.package]`)
+    pub package: Option<WorkspacePackageModel>,
+    /// Lints shared by members (`[workspace.lints]`)
+    pub lints: LintsMap,
+    /// Build profiles of the workspace (`[profile.*]`)
+    pub profile: ProfileMap,
     /// Custom workspace metadata
     pub custom: HashMap<String, toml::Value>,
     pub dependencies: DependencyModelMap,
@@ -45,10 +94,7 @@ impl WorkspaceModel {
         } else if root_path.join("Cargo.toml").exists() {
             root_path.join("Cargo.toml")
         } else {
-            bail!(
-                "Root path must point to Cargo.toml or Magnet.toml: {}",
-                root_path.display()
-            )
+            bail!(MagnetError::ManifestNotFound { path: root_path })
         };
         let config = ManifestConfig::from_file(&source_path)?;
         let config1 = config
@@ -67,9 +113,16 @@ impl WorkspaceModel {
             description: None,
             members: config1.members,
             exclude: config1.exclude,
+            respect_gitignore: config1.respect_gitignore,
             resolver: config1.resolver,
+            root_package: config.package.is_some(),
+            package: config1.package,
+            lints: config1.lints,
+            profile: config.profile,
             custom: config1.custom,
-            dependencies: config1.dependencies.clone()
+            dependencies: config1
+                .dependencies
+                .clone()
                 .into_iter()
                 .map(|(k, v)| (k, v.into()))
                 .collect(),
@@ -83,19 +136,40 @@ impl WorkspaceModel {
     pub fn list_members(&self) -> Result<Vec<PathBuf>> {
         let mut all_members: Vec<PathBuf> = vec![];
         let root_path = &self.root_path;
-        // handle globs
-        for member in self.members.iter() {
-            all_members.extend(glob_relative(root_path, member, true)?);
+        if self.root_package {
+            all_members.push(root_path.clone());
         }
-        // handle excludes
-        for exclude in self.exclude.iter() {
-            let exclude_paths = glob_relative(root_path, exclude, false)?;
-            for exclude_path in exclude_paths {
-                all_members.retain(|path| path != &exclude_path);
+        for path in find_members(
+            root_path,
+            &self.members,
+            &self.exclude,
+            self.respect_gitignore,
+        )? {
+            if !all_members.contains(&path) {
+                all_members.push(path);
             }
         }
         Ok(all_members)
     }
+    /// Members as written to the generated Cargo.toml.
+    ///
+    /// Cargo does not know about Magnet's recursive patterns and .gitignore handling, so in
+    /// that case the members found by Magnet are listed one by one.
+    pub fn cargo_members(&self) -> Result<Vec<String>> {
+        let recursive = self.members.iter().any(|member| member.contains("**"));
+        if !recursive && !self.respect_gitignore {
+            return Ok(self.members.clone());
+        }
+        let mut members = Vec::new();
+        for path in self.list_members()? {
+            if path == self.root_path {
+                continue;
+            }
+            let relative = path.strip_prefix(&self.root_path).unwrap_or(&path);
+            members.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+        Ok(members)
+    }
     /// list packages paths joined with the workspace root path
     pub fn list_packages(&self) -> Result<Vec<PackageModel>> {
         let all_members: Vec<PathBuf> = self.list_members()?;
@@ -118,10 +192,7 @@ impl WorkspaceModel {
             self.name
         )
     }
-    pub fn find_dependency(
-        &self,
-        name: &str,
-    ) -> Option<DependencyModel> {
+    pub fn find_dependency(&self, name: &str) -> Option<DependencyModel> {
         self.dependencies.get(name).cloned()
     }
 }
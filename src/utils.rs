@@ -1,8 +1,9 @@
 //! Utility functions for the magnet CLI tool
 
+use crate::MagnetError;
 use crate::models::ManifestModel;
 use eyre::{ContextCompat, Result, bail};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use tracing::warn;
 use tracing_subscriber::{EnvFilter, fmt};
 
@@ -39,7 +40,7 @@ pub fn setup_logs(log_level: LogLevel) -> Result<()> {
 pub fn find_furthest_manifest(start_dir: &Path) -> Result<(PathBuf, ManifestModel)> {
     let mut current = start_dir.to_path_buf();
 
-    let mut best_found = None;
+    let mut best_found: Option<(PathBuf, ManifestModel)> = None;
     let mut best_score = 0;
     while current.parent().is_some() {
         let Ok(manifest) = ManifestModel::from_dir(&current) else {
@@ -52,6 +53,14 @@ pub fn find_furthest_manifest(start_dir: &Path) -> Result<(PathBuf, ManifestMode
             ManifestModel::Package(_) => 1,
         };
         if new_score == best_score {
+            // A nexus listing the nearer nexus as a member is its parent
+            if let (ManifestModel::Nexus(outer), Some((best_path, _))) = (&manifest, &best_found)
+                && outer
+                    .list_members()
+                    .is_ok_and(|members| members.iter().any(|member| same_path(member, best_path)))
+            {
+                best_found = Some((current.clone(), manifest));
+            }
             current.pop();
             continue;
         }
@@ -64,10 +73,9 @@ pub fn find_furthest_manifest(start_dir: &Path) -> Result<(PathBuf, ManifestMode
     }
     match best_found {
         Some(manifest) => Ok(manifest),
-        None => bail!(
-            "No Magnet.toml/Cargo.toml file found in the directory or any parent directories: {}",
-            start_dir.display()
-        ),
+        None => bail!(MagnetError::ManifestNotFound {
+            path: start_dir.to_path_buf(),
+        }),
     }
 }
 pub fn glob_relative(path: &Path, pattern: &str, allow_error: bool) -> Result<Vec<PathBuf>> {
@@ -84,6 +92,81 @@ pub fn glob_relative(path: &Path, pattern: &str, allow_error: bool) -> Result<Ve
     Ok(result)
 }
 
+/// Directories matched by the `members` patterns of a nexus or workspace rooted at `root_path`.
+///
+/// Recursive `**` patterns only match directories holding a Magnet.toml or Cargo.toml, and
+/// stop at the first manifest found on each path so the members of nested workspaces are left
+/// to them. They never match `target` or hidden directories, which other patterns such as
+/// `.tools/gen` can still list explicitly. Paths matched by `exclude` are removed together
+/// with everything below them, and so are paths ignored by git when `respect_gitignore` is
+/// set.
+pub fn find_members(
+    root_path: &Path,
+    members: &[String],
+    exclude: &[String],
+    respect_gitignore: bool,
+) -> Result<Vec<PathBuf>> {
+    let root_path = root_path.canonicalize()?;
+    let mut found: Vec<PathBuf> = Vec::new();
+    for pattern in members {
+        let recursive = pattern.contains("**");
+        let mut matched: Vec<PathBuf> = Vec::new();
+        for path in glob_relative(&root_path, pattern, true)? {
+            if recursive {
+                let is_manifest_dir =
+                    path.join("Magnet.toml").is_file() || path.join("Cargo.toml").is_file();
+                if path == root_path
+                    || is_build_or_hidden(&root_path, &path)
+                    || !is_manifest_dir
+                    || matched.iter().any(|outer| path.starts_with(outer))
+                {
+                    continue;
+                }
+            }
+            matched.push(path);
+        }
+        for path in matched {
+            if !found.contains(&path) {
+                found.push(path);
+            }
+        }
+    }
+
+    for pattern in exclude {
+        for excluded in glob_relative(&root_path, pattern, false)? {
+            found.retain(|path| !path.starts_with(&excluded));
+        }
+    }
+
+    if respect_gitignore {
+        match crate::git::ignored_paths(&root_path, &found) {
+            Ok(ignored) => found.retain(|path| !ignored.contains(path)),
+            Err(err) => warn!(
+                "Could not check .gitignore for members of {}: {}",
+                root_path.display(),
+                err
+            ),
+        }
+    }
+
+    Ok(found)
+}
+
+/// Whether `path` is inside a `target` or hidden directory below `root_path`
+fn is_build_or_hidden(root_path: &Path, path: &Path) -> bool {
+    path.strip_prefix(root_path)
+        .map(|relative| {
+            relative.components().any(|component| match component {
+                Component::Normal(name) => {
+                    let name = name.to_string_lossy();
+                    name == "target" || name.starts_with('.')
+                }
+                _ => false,
+            })
+        })
+        .unwrap_or(false)
+}
+
 /// Clean up the destination directory before generating files
 pub fn clean_directory(dir: &Path, exclude_patterns: &[&str]) -> Result<()> {
     if !dir.exists() {
@@ -103,7 +186,7 @@ pub fn clean_directory(dir: &Path, exclude_patterns: &[&str]) -> Result<()> {
         // Skip if matches exclude patterns
         if exclude_patterns
             .iter()
-            .any(|pattern| glob::Pattern::new(pattern).map_or(false, |p| p.matches_path(&path)))
+            .any(|pattern| glob::Pattern::new(pattern).is_ok_and(|p| p.matches_path(&path)))
         {
             continue;
         }
@@ -142,10 +225,10 @@ pub fn copy_path(source: &Path, dest: &Path) -> Result<()> {
         }
     } else {
         // Make sure parent directory exists
-        if let Some(parent) = dest.parent() {
-            if !parent.exists() {
-                std::fs::create_dir_all(parent)?;
-            }
+        if let Some(parent) = dest.parent()
+            && !parent.exists()
+        {
+            std::fs::create_dir_all(parent)?;
         }
 
         // Copy the file
@@ -158,10 +241,10 @@ pub fn copy_path(source: &Path, dest: &Path) -> Result<()> {
 /// Create symbolic link to a directory (platform-specific implementation)
 pub fn create_symlink(source: &Path, dest: &Path) -> Result<()> {
     // Make sure parent directory exists
-    if let Some(parent) = dest.parent() {
-        if !parent.exists() {
-            std::fs::create_dir_all(parent)?;
-        }
+    if let Some(parent) = dest.parent()
+        && !parent.exists()
+    {
+        std::fs::create_dir_all(parent)?;
     }
 
     #[cfg(unix)]
@@ -214,3 +297,11 @@ pub fn diff_path(root: &Path, path: &Path) -> PathBuf {
         .with_context(|| format!("Could not diff path: {} {}", root.display(), path.display()))
         .unwrap()
 }
+
+/// Whether two paths point to the same location, resolving symlinks when possible
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
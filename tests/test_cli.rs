@@ -1,6 +1,7 @@
 use std::fs;
 use std::process::Command;
 use eyre::Result;
+use magnet::document::GENERATED_HEADER_PREFIX;
 use tempfile::tempdir;
 
 #[test]
@@ -10,11 +11,7 @@ fn test_magnet_cli_commands() -> Result<()> {
     let magnet_toml_path = temp_dir.path().join("Magnet.toml");
     
     // Get the path to the magnet binary
-    let magnet_bin = if cfg!(windows) {
-        "../../../target/debug/magnet.exe"
-    } else {
-        "../../../target/debug/magnet"
-    };
+    let magnet_bin = env!("CARGO_BIN_EXE_magnet");
     
     // Test the init command
     let output = Command::new(magnet_bin)
@@ -25,11 +22,6 @@ fn test_magnet_cli_commands() -> Result<()> {
         "magnet init failed with: {}", String::from_utf8_lossy(&output.stderr));
     assert!(magnet_toml_path.exists(), "Magnet.toml was not created");
     
-    // Read the generated Magnet.toml and check its contents
-    let content = fs::read_to_string(&magnet_toml_path)?;
-    assert!(content.contains("[project]"), "Magnet.toml missing [project] section");
-    assert!(content.contains("[workspace]"), "Magnet.toml missing [workspace] section");
-    
     // Create a crates directory and some test crates
     let crates_dir = temp_dir.path().join("crates");
     fs::create_dir_all(&crates_dir)?;
@@ -66,36 +58,32 @@ crate1 = { version = "0.1.0" }
     // Update Magnet.toml to include our test crates
     fs::write(
         &magnet_toml_path,
-        r#"[project]
-name = "test-project"
-version = "0.1.0"
-
-[workspace]
+        r#"[workspace]
 members = ["crates/*"]
 exclude = []
 resolver = "2"
 
-[dependencies]
+[workspace.dependencies]
 serde = "1.0"
 "#
     )?;
     
-    // Test the list command
+    // Test the tree command
     let output = Command::new(magnet_bin)
-        .args(["list", "--config", magnet_toml_path.to_str().unwrap()])
+        .args(["tree", temp_dir.path().to_str().unwrap()])
         .current_dir(temp_dir.path())
         .output()?;
     
     assert!(output.status.success(), 
-        "magnet list failed with: {}", String::from_utf8_lossy(&output.stderr));
+        "magnet tree failed with: {}", String::from_utf8_lossy(&output.stderr));
     
     let output_str = String::from_utf8_lossy(&output.stdout);
-    assert!(output_str.contains("crate1"), "List output should include crate1");
-    assert!(output_str.contains("crate2"), "List output should include crate2");
+    assert!(output_str.contains("crate1"), "Tree output should include crate1");
+    assert!(output_str.contains("crate2"), "Tree output should include crate2");
     
     // Test the generate command
     let output = Command::new(magnet_bin)
-        .args(["generate", "--config", magnet_toml_path.to_str().unwrap()])
+        .args(["generate", temp_dir.path().to_str().unwrap()])
         .current_dir(temp_dir.path())
         .output()?;
     
@@ -112,9 +100,52 @@ serde = "1.0"
     assert!(content.contains("members"), "Cargo.toml missing workspace members");
     assert!(content.contains("serde"), "Cargo.toml missing dependencies");
     
+    // The generated files are up to date
+    let output = Command::new(magnet_bin)
+        .args(["generate", "--check", temp_dir.path().to_str().unwrap()])
+        .current_dir(temp_dir.path())
+        .output()?;
+
+    assert!(output.status.success(),
+        "magnet generate --check failed with: {}", String::from_utf8_lossy(&output.stderr));
+
+    // A file generated by another version of magnet is not drift
+    let root_toml = temp_dir.path().join("Cargo.toml");
+    let content = fs::read_to_string(&root_toml)?;
+    assert!(content.starts_with(GENERATED_HEADER_PREFIX),
+        "Cargo.toml missing generated-file header");
+    let other_version = content.replacen(
+        &format!("{} {} ", GENERATED_HEADER_PREFIX, magnet::VERSION),
+        &format!("{} 0.0.1 ", GENERATED_HEADER_PREFIX),
+        1,
+    );
+    assert_ne!(other_version, content);
+    fs::write(&root_toml, other_version)?;
+    let output = Command::new(magnet_bin)
+        .args(["generate", "--check", temp_dir.path().to_str().unwrap()])
+        .current_dir(temp_dir.path())
+        .output()?;
+
+    assert!(output.status.success(),
+        "magnet generate --check failed on another version with: {}", String::from_utf8_lossy(&output.stdout));
+
+    // A hand edit is reported as drift
+    fs::write(&root_toml, content.replace("resolver = \"2\"", "resolver = \"1\""))?;
+    let output = Command::new(magnet_bin)
+        .args(["generate", "--check", temp_dir.path().to_str().unwrap()])
+        .current_dir(temp_dir.path())
+        .output()?;
+
+    assert!(!output.status.success(), "magnet generate --check should fail on drift");
+    assert_eq!(output.status.code(), Some(8), "Drift should exit with the out-of-date code");
+    let output_str = String::from_utf8_lossy(&output.stdout);
+    assert!(output_str.contains("-resolver = \"1\""), "Diff should show the edit");
+    assert!(output_str.contains("+resolver = \"2\""), "Diff should show the edit");
+    fs::write(&root_toml, content)?;
+
     // Test the check command
     let output = Command::new(magnet_bin)
-        .args(["check", "--config", magnet_toml_path.to_str().unwrap()])
+        .args(["check", temp_dir.path().to_str().unwrap()])
         .current_dir(temp_dir.path())
         .output()?;
     
@@ -122,4 +153,441 @@ serde = "1.0"
         "magnet check failed with: {}", String::from_utf8_lossy(&output.stderr));
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_magnet_cli_exit_codes() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let magnet_bin = env!("CARGO_BIN_EXE_magnet");
+
+    // No manifest at all
+    let empty_dir = temp_dir.path().join("empty");
+    fs::create_dir_all(&empty_dir)?;
+    let output = Command::new(magnet_bin)
+        .args(["check", empty_dir.to_str().unwrap()])
+        .output()?;
+    assert_eq!(output.status.code(), Some(3),
+        "Missing manifest: {}", String::from_utf8_lossy(&output.stderr));
+
+    // A manifest that does not parse
+    let invalid_dir = temp_dir.path().join("invalid");
+    fs::create_dir_all(&invalid_dir)?;
+    fs::write(invalid_dir.join("Magnet.toml"), "[workspace\nmembers = ")?;
+    let output = Command::new(magnet_bin)
+        .args(["check", invalid_dir.to_str().unwrap()])
+        .output()?;
+    assert_eq!(output.status.code(), Some(4),
+        "Invalid manifest: {}", String::from_utf8_lossy(&output.stderr));
+
+    // A feature enabling a dependency that does not exist
+    let features_dir = temp_dir.path().join("features");
+    fs::create_dir_all(features_dir.join("app"))?;
+    fs::write(features_dir.join("Magnet.toml"), "[workspace]\nmembers = [\"app\"]\n")?;
+    fs::write(
+        features_dir.join("app/Magnet.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[features]\nextra = [\"dep:missing\"]\n",
+    )?;
+    let output = Command::new(magnet_bin)
+        .args(["generate", features_dir.to_str().unwrap()])
+        .output()?;
+    assert_eq!(output.status.code(), Some(4),
+        "Invalid features: {}", String::from_utf8_lossy(&output.stderr));
+
+    Ok(())
+}
+
+#[test]
+fn test_magnet_cli_add() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    let magnet_bin = env!("CARGO_BIN_EXE_magnet");
+
+    fs::write(
+        root.join("Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+
+[workspace.dependencies]
+serde = "1.0"
+"#,
+    )?;
+    fs::create_dir_all(root.join("crates/app"))?;
+    fs::create_dir_all(root.join("crates/core"))?;
+    fs::write(
+        root.join("crates/core/Magnet.toml"),
+        "[package]\nname = \"core\"\nversion = \"0.1.0\"\n",
+    )?;
+    let app_toml = root.join("crates/app/Magnet.toml");
+    fs::write(
+        &app_toml,
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+# Keep these sorted
+[dependencies]
+anyhow = "1"  # errors
+"#,
+    )?;
+
+    let add = |args: &[&str], dir: &std::path::Path| {
+        Command::new(magnet_bin).arg("add").args(args).current_dir(dir).output()
+    };
+
+    // Dependencies are inserted in place, keeping comments and order
+    let app_dir = root.join("crates/app");
+    let output = add(&["core", "--nexus"], &app_dir)?;
+    assert!(output.status.success(),
+        "magnet add --nexus failed with: {}", String::from_utf8_lossy(&output.stderr));
+    let output = add(&["serde", "--workspace", "-F", "derive,rc"], &app_dir)?;
+    assert!(output.status.success(),
+        "magnet add --workspace failed with: {}", String::from_utf8_lossy(&output.stderr));
+    let output = add(&["tempfile@3", "--dev"], &app_dir)?;
+    assert!(output.status.success(),
+        "magnet add --dev failed with: {}", String::from_utf8_lossy(&output.stderr));
+
+    let content = fs::read_to_string(&app_toml)?;
+    assert!(content.contains("# Keep these sorted\n[dependencies]\nanyhow = \"1\"  # errors\ncore = { nexus = true }\nserde = { workspace = true, features = [\"derive\", \"rc\"] }\n"),
+        "Unexpected [dependencies]:\n{}", content);
+    assert!(content.contains("[dev-dependencies]\ntempfile = \"3\""),
+        "Unexpected [dev-dependencies]:\n{}", content);
+
+    // Features of an existing entry are kept
+    let output = add(&["serde", "--workspace", "-F", "std"], &app_dir)?;
+    assert!(output.status.success(),
+        "magnet add failed with: {}", String::from_utf8_lossy(&output.stderr));
+    let content = fs::read_to_string(&app_toml)?;
+    assert!(content.contains("serde = { workspace = true, features = [\"derive\", \"rc\", \"std\"] }"),
+        "Features should be merged:\n{}", content);
+
+    // Other keys of an existing entry are kept too
+    fs::write(&app_toml, content.replace(
+        "anyhow = \"1\"  # errors\n",
+        "anyhow = \"1\"  # errors\nbar = { version = \"1\", optional = true, default-features = false, features = [\"a\"] }\n",
+    ))?;
+    let output = add(&["bar@1.2", "-F", "b"], &app_dir)?;
+    assert!(output.status.success(),
+        "magnet add failed with: {}", String::from_utf8_lossy(&output.stderr));
+    let content = fs::read_to_string(&app_toml)?;
+    assert!(content.contains("bar = { version = \"1.2\", optional = true, default-features = false, features = [\"a\", \"b\"] }"),
+        "Existing keys should be kept:\n{}", content);
+
+    // Unknown nexus packages and shared definitions are rejected without touching the file
+    let output = add(&["missing", "--nexus"], &app_dir)?;
+    assert_eq!(output.status.code(), Some(5),
+        "Missing nexus package: {}", String::from_utf8_lossy(&output.stderr));
+    let output = add(&["tokio", "--workspace"], &app_dir)?;
+    assert_eq!(output.status.code(), Some(5),
+        "Missing workspace dependency: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fs::read_to_string(&app_toml)?, content);
+
+    // A package picked by name, with a path relative to it, then generated
+    let output = add(&["core", "--path", "crates/core", "--build", "-p", "app", "--generate"], root)?;
+    assert!(output.status.success(),
+        "magnet add --generate failed with: {}", String::from_utf8_lossy(&output.stderr));
+    let content = fs::read_to_string(&app_toml)?;
+    assert!(content.contains("[build-dependencies]\ncore = { path = \"../core\" }"),
+        "Unexpected [build-dependencies]:\n{}", content);
+    let cargo_toml = fs::read_to_string(root.join("crates/app/Cargo.toml"))?;
+    assert!(cargo_toml.contains("[build-dependencies]"),
+        "Cargo.toml should be generated:\n{}", cargo_toml);
+
+    // A package with only a Cargo.toml is never edited
+    let plain_dir = root.join("crates/plain");
+    fs::create_dir_all(&plain_dir)?;
+    let plain_toml = "[package]\nname = \"plain\"\nversion = \"0.1.0\"\n\n[dependencies]\nanyhow = \"1\"\n";
+    fs::write(plain_dir.join("Cargo.toml"), plain_toml)?;
+    let output = add(&["core", "--nexus"], &plain_dir)?;
+    assert!(!output.status.success(), "magnet add should refuse a Cargo.toml-only package");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("magnet import"),
+        "The error should point to magnet import: {}", String::from_utf8_lossy(&output.stderr));
+    let output = Command::new(magnet_bin)
+        .args(["remove", "anyhow"])
+        .current_dir(&plain_dir)
+        .output()?;
+    assert!(!output.status.success(), "magnet remove should refuse a Cargo.toml-only package");
+    assert_eq!(fs::read_to_string(plain_dir.join("Cargo.toml"))?, plain_toml);
+
+    Ok(())
+}
+
+#[test]
+fn test_magnet_cli_remove() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    let magnet_bin = env!("CARGO_BIN_EXE_magnet");
+
+    let nexus_toml = root.join("Magnet.toml");
+    fs::write(
+        &nexus_toml,
+        "[nexus]\nmembers = [\"ws\"]\n\n[dependencies]\nserde = \"1.0\"\ntokio = \"1\" # runtime\n",
+    )?;
+    let ws_toml = root.join("ws/Magnet.toml");
+    fs::create_dir_all(root.join("ws/crates/app"))?;
+    fs::create_dir_all(root.join("ws/crates/cli"))?;
+    fs::write(
+        &ws_toml,
+        "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.dependencies]\nlog = \"0.4\"\n",
+    )?;
+    let app_toml = root.join("ws/crates/app/Magnet.toml");
+    fs::write(
+        &app_toml,
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+# Logging
+log = { workspace = true }
+serde = { workspace = true }
+tokio = { workspace = true }
+
+[dev-dependencies]
+tempfile = "3"
+"#,
+    )?;
+    fs::write(
+        root.join("ws/crates/cli/Magnet.toml"),
+        "[package]\nname = \"cli\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde = { workspace = true }\n",
+    )?;
+
+    let remove = |args: &[&str]| {
+        Command::new(magnet_bin)
+            .arg("remove")
+            .args(args)
+            .current_dir(root.join("ws/crates/app"))
+            .output()
+    };
+
+    // A definition still used by another package is kept
+    let output = remove(&["serde", "--prune"])?;
+    assert!(output.status.success(),
+        "magnet remove failed with: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(!fs::read_to_string(&app_toml)?.contains("serde"), "serde should be removed");
+    assert!(fs::read_to_string(&nexus_toml)?.contains("serde = \"1.0\""),
+        "serde is still used by cli");
+
+    // Without --prune an unused definition is only reported
+    let output = remove(&["log"])?;
+    assert!(output.status.success(),
+        "magnet remove failed with: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("--prune"),
+        "Unused definition should be reported: {}", String::from_utf8_lossy(&output.stdout));
+    assert!(fs::read_to_string(&ws_toml)?.contains("log = \"0.4\""));
+
+    // With --prune an unused nexus-level definition is removed too
+    let output = remove(&["tokio", "--prune"])?;
+    assert!(output.status.success(),
+        "magnet remove failed with: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        fs::read_to_string(&nexus_toml)?,
+        "[nexus]\nmembers = [\"ws\"]\n\n[dependencies]\nserde = \"1.0\"\n"
+    );
+
+    let output = remove(&["tempfile", "--dev"])?;
+    assert!(output.status.success(),
+        "magnet remove --dev failed with: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        fs::read_to_string(&app_toml)?,
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\n\n[dev-dependencies]\n"
+    );
+
+    // Removing a dependency that is not there fails
+    let output = remove(&["tempfile"])?;
+    assert!(!output.status.success(), "Removing a missing dependency should fail");
+
+    // Features referring to the removed dependency are cleaned up
+    fs::write(
+        &app_toml,
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+bar = { version = "1", optional = true }
+
+[features]
+extra = ["dep:bar", "fast"]
+fast = ["bar/simd", "bar?/std"]
+all = ["bar", "extra"]
+"#,
+    )?;
+    let output = remove(&["bar"])?;
+    assert!(output.status.success(),
+        "magnet remove failed with: {}", String::from_utf8_lossy(&output.stderr));
+    let content = fs::read_to_string(&app_toml)?;
+    assert!(content.ends_with("[features]\nextra = [\"fast\"]\nfast = []\nall = [\"extra\"]\n"),
+        "Feature references should be removed:\n{}", content);
+    let output = Command::new(magnet_bin)
+        .args(["generate", root.join("ws").to_str().unwrap()])
+        .output()?;
+    assert!(output.status.success(),
+        "magnet generate failed with: {}", String::from_utf8_lossy(&output.stderr));
+
+    Ok(())
+}
+
+#[test]
+fn test_magnet_cli_import() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path().join("repos");
+    let magnet_bin = env!("CARGO_BIN_EXE_magnet");
+    let write = |path: &str, content: &str| -> Result<()> {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, content)?;
+        Ok(())
+    };
+
+    // Two repositories, the second depending on the first through relative paths
+    write("core/Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\nresolver = \"2\"\n")?;
+    write(
+        "core/crates/types/Cargo.toml",
+        "[package]\nname = \"types\"\nversion = \"0.2.0\"\nedition = \"2021\"\n",
+    )?;
+    write(
+        "core/crates/util/Cargo.toml",
+        "[package]\nname = \"util\"\nversion = \"0.2.0\"\nedition = \"2021\"\n\n[dependencies]\ntypes = { path = \"../types\" }\n",
+    )?;
+    write(
+        "app/Cargo.toml",
+        r#"[workspace]
+members = ["server"]
+
+[workspace.dependencies]
+util = { path = "../core/crates/util" }
+"#,
+    )?;
+    write(
+        "app/server/Cargo.toml",
+        r#"[package]
+name = "server"
+version = "0.1.0"
+edition = "2021"
+
+# Shared types
+[dependencies]
+types = { path = "../../core/crates/types", version = "0.2" }
+util = { workspace = true }
+
+[badges]
+maintenance = { status = "experimental" }
+"#,
+    )?;
+
+    let output = Command::new(magnet_bin)
+        .args(["import", root.to_str().unwrap()])
+        .output()?;
+    assert!(output.status.success(),
+        "magnet import failed with: {}", String::from_utf8_lossy(&output.stderr));
+
+    let nexus = fs::read_to_string(root.join("Magnet.toml"))?;
+    assert!(nexus.contains("[nexus]\nname = \"repos\"\nmembers = [\n    \"app\",\n    \"core\",\n]"),
+        "Unexpected nexus Magnet.toml:\n{}", nexus);
+
+    // Paths inside a repository are kept, paths into another one use the nexus
+    let util = fs::read_to_string(root.join("core/crates/util/Magnet.toml"))?;
+    assert!(util.contains("types = { path = \"../types\" }"), "Unexpected util:\n{}", util);
+    let app = fs::read_to_string(root.join("app/Magnet.toml"))?;
+    assert!(app.contains("util = { nexus = true }"), "Unexpected app:\n{}", app);
+    let server = fs::read_to_string(root.join("app/server/Magnet.toml"))?;
+    assert!(server.contains("# Shared types\n[dependencies]\ntypes = { version = \"0.2\", nexus = true }"),
+        "Unexpected server:\n{}", server);
+
+    // Sections magnet does not generate are reported
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[badges]"), "[badges] should be reported: {}", stdout);
+
+    let output = Command::new(magnet_bin)
+        .args(["generate", root.join("app").to_str().unwrap()])
+        .output()?;
+    assert!(output.status.success(),
+        "magnet generate failed with: {}", String::from_utf8_lossy(&output.stderr));
+    let cargo_toml = fs::read_to_string(root.join("app/server/Cargo.toml"))?;
+    assert!(cargo_toml.contains("../../core/crates/types"),
+        "Nexus dependency should resolve to a path:\n{}", cargo_toml);
+
+    // Importing twice is refused
+    let output = Command::new(magnet_bin)
+        .args(["import", root.to_str().unwrap()])
+        .output()?;
+    assert!(!output.status.success(), "A second import should fail");
+
+    Ok(())
+}
+
+#[test]
+fn test_magnet_cli_new() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    let magnet_bin = env!("CARGO_BIN_EXE_magnet");
+
+    fs::write(
+        root.join("Magnet.toml"),
+        "[nexus]\nmembers = [\"core\"]\nedition = \"2021\"\nlicense = \"MIT\"\n",
+    )?;
+    fs::create_dir_all(root.join("core/crates"))?;
+    fs::write(
+        root.join("core/Magnet.toml"),
+        "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.package]\nedition = \"2024\"\n",
+    )?;
+
+    let new = |args: &[&str], dir: &std::path::Path| {
+        Command::new(magnet_bin).arg("new").args(args).current_dir(dir).output()
+    };
+
+    // A library in an existing workspace, already covered by its members glob
+    let output = new(&["util", "--lib", "--workspace", "core"], root)?;
+    assert!(output.status.success(),
+        "magnet new --lib failed with: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(root.join("core/crates/util/src/lib.rs").exists());
+    let util = fs::read_to_string(root.join("core/crates/util/Magnet.toml"))?;
+    assert!(util.contains("name = \"util\""), "Unexpected util:\n{}", util);
+    assert!(util.contains("edition = { workspace = true }"), "Unexpected util:\n{}", util);
+    assert!(util.contains("license = \"MIT\""), "Unexpected util:\n{}", util);
+    assert!(fs::read_to_string(root.join("core/Magnet.toml"))?.contains("members = [\"crates/*\"]\n"));
+
+    // A binary in the current directory is added to the members of its workspace
+    let output = new(&["tool"], &root.join("core"))?;
+    assert!(output.status.success(),
+        "magnet new failed with: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(root.join("core/tool/src/main.rs").exists());
+    assert!(fs::read_to_string(root.join("core/Magnet.toml"))?.contains("members = [\"crates/*\", \"tool\"]"));
+
+    // A missing workspace is scaffolded with the nexus defaults and added to the nexus
+    let output = new(&["server", "--workspace", "apps"], root)?;
+    assert!(output.status.success(),
+        "magnet new --workspace failed with: {}", String::from_utf8_lossy(&output.stderr));
+    let apps = fs::read_to_string(root.join("apps/Magnet.toml"))?;
+    assert!(apps.contains("[workspace.package]\nedition = \"2021\"\nlicense = \"MIT\""),
+        "Unexpected apps:\n{}", apps);
+    assert!(fs::read_to_string(root.join("Magnet.toml"))?.contains("members = [\"core\", \"apps\"]"));
+    let server = fs::read_to_string(root.join("apps/crates/server/Magnet.toml"))?;
+    assert!(server.contains("license = { workspace = true }"), "Unexpected server:\n{}", server);
+
+    // The scaffolded packages are members of their workspaces
+    for workspace in ["core", "apps"] {
+        let output = Command::new(magnet_bin)
+            .args(["generate", root.join(workspace).to_str().unwrap()])
+            .output()?;
+        assert!(output.status.success(),
+            "magnet generate failed with: {}", String::from_utf8_lossy(&output.stderr));
+    }
+    assert!(root.join("core/tool/Cargo.toml").exists());
+    assert!(root.join("apps/crates/server/Cargo.toml").exists());
+
+    // Existing packages are not overwritten
+    let output = new(&["util", "--workspace", "core"], root)?;
+    assert!(!output.status.success(), "Creating util twice should fail");
+
+    // Names Cargo rejects are refused before anything is written
+    let members = fs::read_to_string(root.join("core/Magnet.toml"))?;
+    for name in ["1bad", "test", "std", "fn"] {
+        let output = new(&[name, "--workspace", "core"], root)?;
+        assert!(!output.status.success(), "magnet new {} should fail", name);
+        assert!(!root.join("core/crates").join(name).exists());
+    }
+    assert_eq!(fs::read_to_string(root.join("core/Magnet.toml"))?, members);
+
+    Ok(())
+}
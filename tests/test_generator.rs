@@ -0,0 +1,1273 @@
+use eyre::Result;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+use magnet::MagnetError;
+use magnet::commands::export::{ExportOptions, export};
+use magnet::document::generated_header;
+use magnet::generator::CargoGenerator;
+use magnet::manager::{ManifestManager, ResolutionMode};
+use magnet::models::{DependencySource, ManifestModel, WorkspaceModel};
+use magnet::utils::find_members;
+
+fn write(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+#[test]
+fn test_generate_all_dependency_kinds() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+
+[workspace.dependencies]
+serde = "1.0"
+"#,
+    )?;
+    write(
+        &root.join("crates/base/Magnet.toml"),
+        r#"[package]
+name = "base"
+version = "0.1.0"
+"#,
+    )?;
+    write(
+        &root.join("crates/app/Magnet.toml"),
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+serde = "1.0"
+
+[dev-dependencies]
+base = { nexus = true }
+
+[build-dependencies]
+cc = "1.0"
+"#,
+    )?;
+
+    let manager = ManifestManager::from_dir(root)?;
+    let workspace = WorkspaceModel::from_dir(root)?;
+    let mut generator = CargoGenerator::new(manager);
+    generator.generate_all(&workspace)?;
+
+    let content = fs::read_to_string(root.join("crates/app/Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    assert_eq!(
+        cargo_toml["dependencies"]["serde"]["version"].as_str(),
+        Some("1.0")
+    );
+    assert_eq!(
+        cargo_toml["dev-dependencies"]["base"]["path"].as_str(),
+        Some("../base")
+    );
+    assert!(
+        cargo_toml["dev-dependencies"]["base"]
+            .get("nexus")
+            .is_none()
+    );
+    assert_eq!(
+        cargo_toml["build-dependencies"]["cc"]["version"].as_str(),
+        Some("1.0")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_target_dependencies() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+"#,
+    )?;
+    write(
+        &root.join("crates/base/Magnet.toml"),
+        r#"[package]
+name = "base"
+version = "0.1.0"
+"#,
+    )?;
+    write(
+        &root.join("crates/app/Magnet.toml"),
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+winapi = { version = "0.3", target = "cfg(windows)" }
+
+[target.'cfg(unix)'.dependencies]
+base = { nexus = true }
+
+[target.'cfg(unix)'.dev-dependencies]
+libc = "0.2"
+"#,
+    )?;
+
+    let manager = ManifestManager::from_dir(root)?;
+    let workspace = WorkspaceModel::from_dir(root)?;
+    let mut generator = CargoGenerator::new(manager);
+    generator.generate_all(&workspace)?;
+
+    let content = fs::read_to_string(root.join("crates/app/Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    let unix = &cargo_toml["target"]["cfg(unix)"];
+    assert_eq!(
+        unix["dependencies"]["base"]["path"].as_str(),
+        Some("../base")
+    );
+    assert_eq!(
+        unix["dev-dependencies"]["libc"]["version"].as_str(),
+        Some("0.2")
+    );
+    let windows = &cargo_toml["target"]["cfg(windows)"];
+    assert_eq!(
+        windows["dependencies"]["winapi"]["version"].as_str(),
+        Some("0.3")
+    );
+    assert!(windows["dependencies"]["winapi"].get("target").is_none());
+    assert!(cargo_toml.get("dependencies").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_features_and_build_targets() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+"#,
+    )?;
+    write(
+        &root.join("crates/app/Magnet.toml"),
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+serde = { version = "1.0", optional = true }
+
+[features]
+default = ["std"]
+std = []
+serde = ["dep:serde", "serde/derive"]
+
+[lib]
+path = "src/lib.rs"
+crate-type = ["rlib", "cdylib"]
+
+[[bin]]
+name = "app-cli"
+path = "src/bin/cli.rs"
+required-features = ["std"]
+
+[[bin]]
+name = "app-server"
+path = "src/bin/server.rs"
+
+[[bench]]
+name = "throughput"
+harness = false
+"#,
+    )?;
+
+    let manager = ManifestManager::from_dir(root)?;
+    let workspace = WorkspaceModel::from_dir(root)?;
+    let mut generator = CargoGenerator::new(manager);
+    generator.generate_all(&workspace)?;
+
+    let content = fs::read_to_string(root.join("crates/app/Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    assert_eq!(cargo_toml["features"]["serde"].as_array().unwrap().len(), 2);
+    assert_eq!(cargo_toml["lib"]["crate-type"].as_array().unwrap().len(), 2);
+    let bins = cargo_toml["bin"].as_array().unwrap();
+    assert_eq!(bins.len(), 2);
+    assert_eq!(bins[0]["name"].as_str(), Some("app-cli"));
+    assert_eq!(bins[0]["required-features"][0].as_str(), Some("std"));
+    assert_eq!(cargo_toml["bench"][0]["harness"].as_bool(), Some(false));
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_rejects_unknown_feature_dependency() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+"#,
+    )?;
+    write(
+        &root.join("crates/app/Magnet.toml"),
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dev-dependencies]
+tokio = "1"
+
+[features]
+async = ["dep:tokio"]
+"#,
+    )?;
+
+    let manager = ManifestManager::from_dir(root)?;
+    let workspace = WorkspaceModel::from_dir(root)?;
+    let mut generator = CargoGenerator::new(manager);
+    let err = generator.generate_all(&workspace).unwrap_err();
+    assert!(
+        format!("{:?}", err).contains("'tokio' is not a dependency"),
+        "unexpected error: {:?}",
+        err
+    );
+
+    // `dep:` and bare entries need the dependency to be optional
+    write(
+        &root.join("crates/app/Magnet.toml"),
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+tokio = "1"
+serde = "1"
+
+[features]
+async = ["dep:tokio", "serde/derive"]
+json = ["serde"]
+"#,
+    )?;
+    let manager = ManifestManager::from_dir(root)?;
+    let mut generator = CargoGenerator::new(manager);
+    let err = format!("{:?}", generator.generate_all(&workspace).unwrap_err());
+    assert!(
+        err.contains("enables 'dep:tokio', but 'tokio' is not an optional dependency"),
+        "unexpected error: {}",
+        err
+    );
+    assert!(
+        err.contains("enables 'serde', but 'serde' is not an optional dependency"),
+        "unexpected error: {}",
+        err
+    );
+    assert!(!err.contains("serde/derive"), "unexpected error: {}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_preserves_format() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+"#,
+    )?;
+    write(
+        &root.join("crates/app/Magnet.toml"),
+        r#"[package]
+name = "app"
+version = "0.2.0"
+edition = "2024"
+
+[dependencies]
+serde = "1.0"
+tokio = { version = "1", features = ["full"] }
+anyhow = "1"
+"#,
+    )?;
+    let existing = r#"# Hand-written header comment
+[package]
+name = "app" # the name
+version = "0.1.0"
+edition = "2024"
+description = ""
+authors = []
+
+[badges]
+maintenance = { status = "actively-developed" }
+
+[dependencies]
+# serialization
+serde = "1.0"
+tokio = { version = "1", features = ["full"] }
+removed = "0.1"
+"#;
+    write(&root.join("crates/app/Cargo.toml"), existing)?;
+
+    let manager = ManifestManager::from_dir(root)?;
+    let workspace = WorkspaceModel::from_dir(root)?;
+    let mut generator = CargoGenerator::new(manager).with_preserve_format(true);
+    generator.generate_all(&workspace)?;
+
+    let content = fs::read_to_string(root.join("crates/app/Cargo.toml"))?;
+    assert_eq!(
+        content,
+        generated_header(Path::new("Magnet.toml"))
+            + r#"
+# Hand-written header comment
+[package]
+name = "app" # the name
+version = "0.2.0"
+edition = "2024"
+description = ""
+authors = []
+
+[badges]
+maintenance = { status = "actively-developed" }
+
+[dependencies]
+# serialization
+serde = "1.0"
+tokio = { version = "1", features = ["full"] }
+anyhow = "1"
+"#
+    );
+
+    // A second run leaves the file untouched
+    generator.generate_all(&workspace)?;
+    assert_eq!(
+        fs::read_to_string(root.join("crates/app/Cargo.toml"))?,
+        content
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_workspace_package_inheritance() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+
+[workspace.package]
+version = "1.2.3"
+edition = "2021"
+license = "MIT"
+rust-version = "1.80"
+"#,
+    )?;
+    write(
+        &root.join("crates/app/Magnet.toml"),
+        r#"[package]
+name = "app"
+version.workspace = true
+edition.workspace = true
+license = { workspace = true }
+rust-version.workspace = true
+"#,
+    )?;
+
+    let workspace = WorkspaceModel::from_dir(root)?;
+    let package = workspace.find_package("app")?;
+    assert_eq!(package.version, "1.2.3");
+    assert_eq!(package.edition, "2021");
+    assert_eq!(package.rust_version.as_deref(), Some("1.80"));
+
+    // Inheritance is kept by default
+    let manager = ManifestManager::from_dir(root)?;
+    let mut generator = CargoGenerator::new(manager);
+    generator.generate_all(&workspace)?;
+
+    let content = fs::read_to_string(root.join("Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    assert_eq!(
+        cargo_toml["workspace"]["package"]["version"].as_str(),
+        Some("1.2.3")
+    );
+    let content = fs::read_to_string(root.join("crates/app/Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    assert_eq!(
+        cargo_toml["package"]["version"]["workspace"].as_bool(),
+        Some(true)
+    );
+    assert_eq!(
+        cargo_toml["package"]["rust-version"]["workspace"].as_bool(),
+        Some(true)
+    );
+
+    // Or written out as concrete values
+    let manager = ManifestManager::from_dir(root)?;
+    let mut generator = CargoGenerator::new(manager).with_expand_workspace_fields(true);
+    generator.generate_all(&workspace)?;
+
+    let content = fs::read_to_string(root.join("crates/app/Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    assert_eq!(cargo_toml["package"]["version"].as_str(), Some("1.2.3"));
+    assert_eq!(cargo_toml["package"]["edition"].as_str(), Some("2021"));
+    assert_eq!(cargo_toml["package"]["license"].as_str(), Some("MIT"));
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_lints() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[nexus]
+name = "nexus"
+members = ["ws"]
+
+[nexus.lints.clippy]
+unwrap_used = "warn"
+todo = "deny"
+"#,
+    )?;
+    write(
+        &root.join("ws/Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+
+[workspace.lints.clippy]
+todo = "allow"
+
+[workspace.lints.rust]
+unsafe_code = { level = "forbid", priority = -1 }
+"#,
+    )?;
+    write(
+        &root.join("ws/crates/shared/Magnet.toml"),
+        r#"[package]
+name = "shared"
+version = "0.1.0"
+"#,
+    )?;
+    write(
+        &root.join("ws/crates/strict/Magnet.toml"),
+        r#"[package]
+name = "strict"
+version = "0.1.0"
+
+[lints.clippy]
+unwrap_used = "deny"
+"#,
+    )?;
+
+    let workspace_root = root.join("ws");
+    let workspace = WorkspaceModel::from_dir(&workspace_root)?;
+    let manager = ManifestManager::from_dir(&workspace_root)?;
+    let mut generator = CargoGenerator::new(manager);
+    generator.generate_all(&workspace)?;
+
+    // Nexus lints are the defaults, the workspace overrides them
+    let content = fs::read_to_string(workspace_root.join("Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    let lints = &cargo_toml["workspace"]["lints"];
+    assert_eq!(lints["clippy"]["unwrap_used"].as_str(), Some("warn"));
+    assert_eq!(lints["clippy"]["todo"].as_str(), Some("allow"));
+    assert_eq!(
+        lints["rust"]["unsafe_code"]["level"].as_str(),
+        Some("forbid")
+    );
+    assert_eq!(
+        lints["rust"]["unsafe_code"]["priority"].as_integer(),
+        Some(-1)
+    );
+
+    // Packages without lints follow the workspace
+    let content = fs::read_to_string(workspace_root.join("crates/shared/Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    assert_eq!(cargo_toml["lints"]["workspace"].as_bool(), Some(true));
+
+    // Package lints are merged over the workspace lints
+    let content = fs::read_to_string(workspace_root.join("crates/strict/Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    assert_eq!(
+        cargo_toml["lints"]["clippy"]["unwrap_used"].as_str(),
+        Some("deny")
+    );
+    assert_eq!(
+        cargo_toml["lints"]["clippy"]["todo"].as_str(),
+        Some("allow")
+    );
+    assert_eq!(
+        cargo_toml["lints"]["rust"]["unsafe_code"]["level"].as_str(),
+        Some("forbid")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_profiles() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[nexus]
+members = ["ws"]
+
+[profile.release]
+lto = "thin"
+codegen-units = 1
+
+[profile.release.package.heavy]
+opt-level = 3
+
+[profile.bench]
+debug = true
+"#,
+    )?;
+    write(
+        &root.join("ws/Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+
+[profile.release]
+lto = true
+
+[profile.release.package.heavy]
+debug = false
+"#,
+    )?;
+    write(
+        &root.join("ws/crates/app/Magnet.toml"),
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[profile.dev]
+opt-level = 1
+"#,
+    )?;
+
+    let workspace_root = root.join("ws");
+    let workspace = WorkspaceModel::from_dir(&workspace_root)?;
+    let manager = ManifestManager::from_dir(&workspace_root)?;
+    let mut generator = CargoGenerator::new(manager);
+    generator.generate_all(&workspace)?;
+
+    let content = fs::read_to_string(workspace_root.join("Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    let release = &cargo_toml["profile"]["release"];
+    assert_eq!(release["lto"].as_bool(), Some(true));
+    assert_eq!(release["codegen-units"].as_integer(), Some(1));
+    assert_eq!(
+        release["package"]["heavy"]["opt-level"].as_integer(),
+        Some(3)
+    );
+    assert_eq!(release["package"]["heavy"]["debug"].as_bool(), Some(false));
+    assert_eq!(
+        cargo_toml["profile"]["bench"]["debug"].as_bool(),
+        Some(true)
+    );
+
+    // Cargo ignores package profiles, so they are not generated
+    let content = fs::read_to_string(workspace_root.join("crates/app/Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    assert!(cargo_toml.get("profile").is_none());
+
+    // Exporting a package gets the same merged profiles as its workspace
+    let generated = fs::read_to_string(workspace_root.join("Cargo.toml"))?;
+    let generated: toml::Value = toml::from_str(&generated)?;
+    let export_dir = root.join("export");
+    export(&ExportOptions {
+        package_path: workspace_root.join("crates/app"),
+        export_dir: Some(export_dir.clone()),
+        ..ExportOptions::default()
+    })?;
+    let content = fs::read_to_string(export_dir.join("Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    assert_eq!(cargo_toml["profile"], generated["profile"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_nexus_dependencies() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[nexus]
+members = ["ws"]
+
+[dependencies]
+serde = "1.0"
+tokio = { version = "1", features = ["rt"] }
+shared = { path = "libs/shared" }
+"#,
+    )?;
+    write(
+        &root.join("libs/shared/Magnet.toml"),
+        r#"[package]
+name = "shared"
+version = "0.1.0"
+"#,
+    )?;
+    write(
+        &root.join("ws/Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+
+[workspace.dependencies]
+serde = "1.0.200"
+"#,
+    )?;
+    write(
+        &root.join("ws/crates/app/Magnet.toml"),
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+serde = { workspace = true }
+tokio = { workspace = true, features = ["macros"] }
+shared = { workspace = true }
+"#,
+    )?;
+
+    let workspace_root = root.join("ws");
+    let workspace = WorkspaceModel::from_dir(&workspace_root)?;
+    let manager = ManifestManager::from_dir(&workspace_root)?;
+    let mut generator = CargoGenerator::new(manager);
+    generator.generate_all(&workspace)?;
+
+    // Workspaces inherit the nexus dependencies and override them
+    let content = fs::read_to_string(workspace_root.join("Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    let dependencies = &cargo_toml["workspace"]["dependencies"];
+    assert_eq!(dependencies["serde"]["version"].as_str(), Some("1.0.200"));
+    assert_eq!(dependencies["tokio"]["version"].as_str(), Some("1"));
+    assert_eq!(
+        dependencies["shared"]["path"].as_str(),
+        Some("../libs/shared")
+    );
+
+    let content = fs::read_to_string(workspace_root.join("crates/app/Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    let dependencies = &cargo_toml["dependencies"];
+    assert_eq!(dependencies["serde"]["version"].as_str(), Some("1.0.200"));
+    let tokio_features: Vec<_> = dependencies["tokio"]["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|feature| feature.as_str())
+        .collect();
+    assert_eq!(tokio_features, ["rt", "macros"]);
+    assert_eq!(
+        dependencies["shared"]["path"].as_str(),
+        Some("../../../libs/shared")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_resolves_workspace_dependencies() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[nexus]
+members = ["lib", "ws"]
+
+[dependencies]
+util = { nexus = true, fallback = ["nexus", "registry"], version = "0.1" }
+"#,
+    )?;
+    write(
+        &root.join("lib/Magnet.toml"),
+        "[workspace]\nmembers = [\"crates/*\"]\n",
+    )?;
+    for name in ["core", "util"] {
+        write(
+            &root.join("lib/crates").join(name).join("Magnet.toml"),
+            &format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", name),
+        )?;
+    }
+    write(
+        &root.join("ws/Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+
+[workspace.dependencies]
+core = { version = "0.1", nexus = true, fallback = ["nexus", "registry"], version-check = false }
+"#,
+    )?;
+    write(
+        &root.join("ws/crates/app/Magnet.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+    )?;
+
+    let workspace_root = root.join("ws");
+    let workspace = WorkspaceModel::from_dir(&workspace_root)?;
+    let manager = ManifestManager::from_dir(&workspace_root)?;
+    let mut generator = CargoGenerator::new(manager);
+    generator.generate_all(&workspace)?;
+
+    // Shared definitions, including the inherited nexus ones, are resolved for Cargo
+    let content = fs::read_to_string(workspace_root.join("Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    let dependencies = &cargo_toml["workspace"]["dependencies"];
+    assert_eq!(
+        dependencies["core"]["path"].as_str(),
+        Some("../lib/crates/core")
+    );
+    assert_eq!(
+        dependencies["util"]["path"].as_str(),
+        Some("../lib/crates/util")
+    );
+    for key in ["nexus", "fallback", "version-check"] {
+        assert!(!content.contains(key), "{} in:\n{}", key, content);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_combined_root_manifests() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    // The nexus root is also a workspace whose root is a package
+    write(
+        &root.join("Magnet.toml"),
+        r#"[nexus]
+members = ["other"]
+
+[workspace]
+members = ["crates/*"]
+
+[package]
+name = "root-app"
+version = "0.1.0"
+
+[dependencies]
+lib = { path = "crates/lib" }
+"#,
+    )?;
+    write(
+        &root.join("crates/lib/Magnet.toml"),
+        r#"[package]
+name = "lib"
+version = "0.1.0"
+"#,
+    )?;
+    write(
+        &root.join("other/Magnet.toml"),
+        r#"[workspace]
+members = ["tool"]
+"#,
+    )?;
+    write(
+        &root.join("other/tool/Magnet.toml"),
+        r#"[package]
+name = "tool"
+version = "0.1.0"
+
+[dependencies]
+root-app = { nexus = true }
+lib = { nexus = true }
+"#,
+    )?;
+
+    let ManifestModel::Nexus(nexus) = ManifestModel::from_dir(root)? else {
+        panic!("expected the root to be a nexus");
+    };
+    let workspaces: Vec<_> = nexus.list_workspaces()?;
+    assert_eq!(workspaces.len(), 2);
+    assert!(workspaces[0].root_package);
+    let mut packages: Vec<_> = nexus
+        .list_all_packages()?
+        .into_iter()
+        .map(|package| package.name)
+        .collect();
+    packages.sort();
+    assert_eq!(packages, ["lib", "root-app", "tool"]);
+
+    // The root Cargo.toml holds both the workspace and the package
+    let manager = ManifestManager::from_dir(root)?;
+    let mut generator = CargoGenerator::new(manager);
+    generator.generate_all(&WorkspaceModel::from_dir(root)?)?;
+    let content = fs::read_to_string(root.join("Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    assert!(cargo_toml.get("nexus").is_none());
+    assert_eq!(
+        cargo_toml["workspace"]["members"][0].as_str(),
+        Some("crates/*")
+    );
+    assert_eq!(cargo_toml["package"]["name"].as_str(), Some("root-app"));
+    assert_eq!(
+        cargo_toml["dependencies"]["lib"]["path"].as_str(),
+        Some("crates/lib")
+    );
+
+    // Packages of the root workspace are found from other workspaces
+    let other = root.join("other");
+    let manager = ManifestManager::from_dir(&other)?;
+    let mut generator = CargoGenerator::new(manager);
+    generator.generate_all(&WorkspaceModel::from_dir(&other)?)?;
+    let content = fs::read_to_string(other.join("tool/Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    assert_eq!(
+        cargo_toml["dependencies"]["root-app"]["path"].as_str(),
+        Some("../..")
+    );
+    assert_eq!(
+        cargo_toml["dependencies"]["lib"]["path"].as_str(),
+        Some("../../crates/lib")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_publish_mode() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+"#,
+    )?;
+    write(
+        &root.join("crates/base/Magnet.toml"),
+        r#"[package]
+name = "base"
+version = "0.3.1"
+"#,
+    )?;
+    write(
+        &root.join("crates/app/Magnet.toml"),
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+base = { nexus = true, features = ["std"] }
+"#,
+    )?;
+
+    let workspace = WorkspaceModel::from_dir(root)?;
+    let read_base_dependency = || -> Result<toml::Value> {
+        let content = fs::read_to_string(root.join("crates/app/Cargo.toml"))?;
+        let cargo_toml: toml::Value = toml::from_str(&content)?;
+        Ok(cargo_toml["dependencies"]["base"].clone())
+    };
+
+    let manager = ManifestManager::from_dir(root)?.with_mode(ResolutionMode::Publish);
+    CargoGenerator::new(manager).generate_all(&workspace)?;
+    let base = read_base_dependency()?;
+    assert_eq!(base["version"].as_str(), Some("0.3.1"));
+    assert!(base.get("path").is_none());
+    assert_eq!(base["features"][0].as_str(), Some("std"));
+
+    let manager = ManifestManager::from_dir(root)?
+        .with_mode(ResolutionMode::Publish)
+        .with_keep_path(true);
+    CargoGenerator::new(manager).generate_all(&workspace)?;
+    let base = read_base_dependency()?;
+    assert_eq!(base["version"].as_str(), Some("0.3.1"));
+    assert_eq!(base["path"].as_str(), Some("../base"));
+
+    // Packages that cannot be published cannot be depended on by version
+    write(
+        &root.join("crates/base/Magnet.toml"),
+        r#"[package]
+name = "base"
+version = "0.3.1"
+publish = false
+"#,
+    )?;
+    let manager = ManifestManager::from_dir(root)?.with_mode(ResolutionMode::Publish);
+    let err = CargoGenerator::new(manager)
+        .generate_all(&workspace)
+        .unwrap_err();
+    assert!(format!("{:?}", err).contains("publish = false"));
+
+    // Exporting in publish mode fails the same way
+    let err = export(&ExportOptions {
+        package_path: root.to_path_buf(),
+        export_dir: Some(root.join("export")),
+        mode: ResolutionMode::Publish,
+        ..ExportOptions::default()
+    })
+    .unwrap_err();
+    assert!(
+        matches!(
+            MagnetError::find(&err),
+            Some(MagnetError::UnresolvedDependency { name, .. }) if name == "base"
+        ),
+        "unexpected error: {:?}",
+        err
+    );
+
+    Ok(())
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = std::process::Command::new("git")
+        .current_dir(dir)
+        .args([
+            "-c",
+            "user.name=magnet",
+            "-c",
+            "user.email=magnet@example.com",
+        ])
+        .args(args)
+        .output()?;
+    eyre::ensure!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[test]
+fn test_generate_git_mode() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[nexus]
+members = ["libs", "app"]
+"#,
+    )?;
+    // A member workspace living in its own repository, published at a file:// remote
+    let libs = root.join("libs");
+    write(
+        &libs.join("Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+"#,
+    )?;
+    write(
+        &libs.join("crates/base/Magnet.toml"),
+        r#"[package]
+name = "base"
+version = "0.1.0"
+"#,
+    )?;
+    git(&libs, &["init", "-q"])?;
+    git(&libs, &["add", "."])?;
+    git(&libs, &["commit", "-q", "-m", "init"])?;
+    let remote = root.join("remote/libs.git");
+    git(root, &["clone", "-q", "--bare", "libs", "remote/libs.git"])?;
+    let url = format!("file://{}", remote.display());
+    git(&libs, &["remote", "add", "origin", &url])?;
+    let head = git(&libs, &["rev-parse", "HEAD"])?;
+
+    write(
+        &root.join("app/Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+"#,
+    )?;
+    write(
+        &root.join("app/crates/cli/Magnet.toml"),
+        r#"[package]
+name = "cli"
+version = "0.1.0"
+
+[dependencies]
+base = { nexus = true }
+"#,
+    )?;
+
+    let app = root.join("app");
+    let workspace = WorkspaceModel::from_dir(&app)?;
+    let read_base_dependency = || -> Result<toml::Value> {
+        let content = fs::read_to_string(app.join("crates/cli/Cargo.toml"))?;
+        let cargo_toml: toml::Value = toml::from_str(&content)?;
+        Ok(cargo_toml["dependencies"]["base"].clone())
+    };
+
+    let manager = ManifestManager::from_dir(&app)?.with_mode(ResolutionMode::Git);
+    CargoGenerator::new(manager).generate_all(&workspace)?;
+    let base = read_base_dependency()?;
+    assert_eq!(base["git"].as_str(), Some(url.as_str()));
+    assert_eq!(base["rev"].as_str(), Some(head.as_str()));
+    assert_eq!(base["package"].as_str(), Some("base"));
+    assert!(base.get("path").is_none());
+
+    // A tag on the checked out commit is used instead of the commit
+    git(&libs, &["tag", "v0.1.0"])?;
+    let manager = ManifestManager::from_dir(&app)?.with_mode(ResolutionMode::Git);
+    CargoGenerator::new(manager).generate_all(&workspace)?;
+    let base = read_base_dependency()?;
+    assert_eq!(base["tag"].as_str(), Some("v0.1.0"));
+    assert!(base.get("rev").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_member_discovery() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[nexus]
+name = "nexus"
+members = ["*"]
+exclude = ["old"]
+"#,
+    )?;
+    write(
+        &root.join("ws/Magnet.toml"),
+        r#"[workspace]
+members = ["**"]
+exclude = ["vendor"]
+respect-gitignore = true
+"#,
+    )?;
+    write(&root.join("ws/.gitignore"), "ignored/\n")?;
+    for member in [
+        "crates/a",
+        "crates/a/fuzz",
+        "tools/b",
+        "vendor/c",
+        "ignored/d",
+        "target/package/e",
+        ".hidden/f",
+    ] {
+        let name = Path::new(member).file_name().unwrap().to_string_lossy();
+        write(
+            &root.join("ws").join(member).join("Magnet.toml"),
+            &format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", name),
+        )?;
+    }
+    write(&root.join("old/Magnet.toml"), "[workspace]\nmembers = []\n")?;
+    git(root, &["init", "-q"])?;
+
+    // Excluded members of the nexus are skipped
+    let ManifestModel::Nexus(nexus) = ManifestModel::from_dir(root)? else {
+        panic!("expected a nexus");
+    };
+    let workspaces: Vec<String> = nexus
+        .list_workspaces()?
+        .into_iter()
+        .map(|workspace| workspace.name)
+        .collect();
+    assert_eq!(workspaces, ["ws"]);
+
+    // `**` finds the outermost manifests, minus excluded, ignored, build and hidden paths
+    let workspace_root = root.join("ws");
+    let workspace = WorkspaceModel::from_dir(&workspace_root)?;
+    let mut packages: Vec<String> = workspace
+        .list_packages()?
+        .into_iter()
+        .map(|package| package.name)
+        .collect();
+    packages.sort();
+    assert_eq!(packages, ["a", "b"]);
+
+    // Hidden and build directories listed explicitly are kept
+    let members = find_members(
+        &workspace_root,
+        &[".hidden/f".to_string(), "target/*".to_string()],
+        &[],
+        false,
+    )?;
+    let workspace_root = workspace_root.canonicalize()?;
+    assert_eq!(
+        members,
+        [
+            workspace_root.join(".hidden/f"),
+            workspace_root.join("target/package")
+        ]
+    );
+
+    // Cargo gets the members found by magnet, not the pattern
+    let manager = ManifestManager::from_dir(&workspace_root)?;
+    let mut generator = CargoGenerator::new(manager);
+    generator.generate_all(&workspace)?;
+    let content = fs::read_to_string(workspace_root.join("Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    let workspace_table = &cargo_toml["workspace"];
+    let members: Vec<&str> = workspace_table["members"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(toml::Value::as_str)
+        .collect();
+    assert_eq!(members, ["crates/a", "tools/b"]);
+    assert!(workspace_table.get("respect-gitignore").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_renamed_dependencies() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[nexus]
+name = "nexus"
+members = ["common", "app"]
+"#,
+    )?;
+    write(
+        &root.join("common/Magnet.toml"),
+        "[workspace]\nmembers = [\"crates/*\"]\n",
+    )?;
+    write(
+        &root.join("common/crates/shared-types/Magnet.toml"),
+        "[package]\nname = \"shared-types\"\nversion = \"0.1.0\"\n",
+    )?;
+    write(
+        &root.join("app/Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+
+[workspace.dependencies]
+shared-types = { nexus = true }
+"#,
+    )?;
+    write(
+        &root.join("app/crates/server/Magnet.toml"),
+        r#"[package]
+name = "server"
+version = "0.1.0"
+
+[dependencies]
+types = { package = "shared-types", nexus = true }
+model = { package = "shared-types", workspace = true }
+"#,
+    )?;
+
+    let workspace_root = root.join("app");
+    let workspace = WorkspaceModel::from_dir(&workspace_root)?;
+    let manager = ManifestManager::from_dir(&workspace_root)?;
+    let mut generator = CargoGenerator::new(manager);
+    generator.generate_all(&workspace)?;
+
+    // Both renames resolve to the real package and keep the alias
+    let content = fs::read_to_string(workspace_root.join("crates/server/Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    for alias in ["types", "model"] {
+        let dep = &cargo_toml["dependencies"][alias];
+        assert_eq!(dep["package"].as_str(), Some("shared-types"), "{}", alias);
+        assert_eq!(
+            dep["path"].as_str(),
+            Some("../../../common/crates/shared-types"),
+            "{}",
+            alias
+        );
+        assert!(dep.get("nexus").is_none(), "{}", alias);
+        assert!(dep.get("workspace").is_none(), "{}", alias);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_missing_nexus_member_fallbacks() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    // `private` is a member nobody without access has checked out
+    write(
+        &root.join("Magnet.toml"),
+        r#"[nexus]
+name = "nexus"
+members = ["app", "private"]
+"#,
+    )?;
+    write(
+        &root.join("app/Magnet.toml"),
+        "[workspace]\nmembers = [\"crates/*\"]\n",
+    )?;
+    write(
+        &root.join("app/crates/server/Magnet.toml"),
+        r#"[package]
+name = "server"
+version = "0.1.0"
+
+[dependencies]
+secret = { nexus = true, version = "1.2", fallback = ["nexus", "registry"] }
+vault = { nexus = true, git = "https://example.com/vault.git", fallback = ["nexus", "git"] }
+"#,
+    )?;
+
+    let ManifestModel::Nexus(nexus) = ManifestModel::from_dir(root)? else {
+        panic!("expected a nexus");
+    };
+    assert_eq!(
+        nexus.missing_members(),
+        [root.canonicalize()?.join("private")]
+    );
+
+    let workspace_root = root.join("app");
+    let workspace = WorkspaceModel::from_dir(&workspace_root)?;
+    let manager = ManifestManager::from_dir(&workspace_root)?.with_strict(true);
+    let mut generator = CargoGenerator::new(manager.clone());
+    generator.generate_all(&workspace)?;
+
+    let content = fs::read_to_string(workspace_root.join("crates/server/Cargo.toml"))?;
+    let cargo_toml: toml::Value = toml::from_str(&content)?;
+    let dependencies = &cargo_toml["dependencies"];
+    assert_eq!(dependencies["secret"]["version"].as_str(), Some("1.2"));
+    assert!(dependencies["secret"].get("nexus").is_none());
+    assert!(dependencies["secret"].get("fallback").is_none());
+    assert_eq!(
+        dependencies["vault"]["git"].as_str(),
+        Some("https://example.com/vault.git")
+    );
+    // The manager reports the missing member once, and which dependencies used their fallback
+    assert_eq!(
+        manager.missing_members(),
+        [root.canonicalize()?.join("private")]
+    );
+    let fallbacks: Vec<(String, DependencySource)> = manager
+        .fallbacks()
+        .into_iter()
+        .map(|fallback| (fallback.name, fallback.source))
+        .collect();
+    assert_eq!(
+        fallbacks,
+        [
+            ("secret".to_string(), DependencySource::Registry),
+            ("vault".to_string(), DependencySource::Git)
+        ]
+    );
+
+    // In strict mode, optional dependencies that cannot be resolved fail like any other
+    let server_toml = workspace_root.join("crates/server/Magnet.toml");
+    let mut content = fs::read_to_string(&server_toml)?;
+    content.push_str("extra = { nexus = true, optional = true }\n");
+    write(&server_toml, &content)?;
+    let manager = ManifestManager::from_dir(&workspace_root)?.with_strict(true);
+    let err = CargoGenerator::new(manager)
+        .generate_all(&workspace)
+        .unwrap_err();
+    assert!(
+        matches!(
+            MagnetError::find(&err),
+            Some(MagnetError::UnresolvedDependency { name, .. }) if name == "extra"
+        ),
+        "unexpected error: {:?}",
+        err
+    );
+
+    Ok(())
+}
@@ -15,7 +15,7 @@ fn test_magnet_config_create_and_parse() -> Result<()> {
     // Create package section
     config.package = Some(magnet::configs::PackageConfig {
         name: "test-project".to_string(),
-        version: "0.1.0".to_string(),
+        version: "0.1.0".into(),
         ..Default::default()
     });
     // Create workspace section
@@ -35,7 +35,7 @@ fn test_magnet_config_create_and_parse() -> Result<()> {
     assert!(read_config.package.is_some());
     if let Some(package) = &read_config.package {
         assert_eq!(package.name, "test-project".to_string());
-        assert_eq!(package.version, "0.1.0".to_string());
+        assert_eq!(package.version, "0.1.0".into());
     }
     assert!(read_config.workspace.is_some());
     if let Some(workspace) = &read_config.workspace {
@@ -58,7 +58,7 @@ fn test_workspace_crate_detection() -> Result<()> {
     let mut config = ManifestConfig::new();
     config.package = Some(magnet::configs::PackageConfig {
         name: "test-workspace".to_string(),
-        version: "0.1.0".to_string(),
+        version: "0.1.0".into(),
         ..Default::default()
     });
     config.workspace = Some(magnet::configs::WorkspaceConfig {
@@ -109,11 +109,15 @@ crate1 = { version = "0.1.0" }
         description: Some("Test workspace".to_string()),
         members: vec!["crates/*".to_string()],
         exclude: vec![],
+        respect_gitignore: false,
         resolver: Some("2".to_string()),
-        paths: Default::default(),
+        root_package: false,
+        package: None,
+        lints: Default::default(),
+        profile: Default::default(),
         custom: Default::default(),
         dependencies: Default::default(),
-        patch: None,
+        patch: Default::default(),
         root_path: workspace_dir.clone(),
         source_path: workspace_dir.join("Magnet.toml"),
     };
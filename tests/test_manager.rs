@@ -0,0 +1,438 @@
+use eyre::Result;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tempfile::tempdir;
+
+use magnet::MagnetError;
+use magnet::manager::ManifestManager;
+use magnet::models::{DependencyModel, ManifestModel};
+
+fn write(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Move the modification time of a file or directory forward, so that changes made within
+/// the timestamp granularity of the file system are still noticed
+fn touch(path: &Path) -> Result<()> {
+    let later = SystemTime::now() + Duration::from_secs(10);
+    fs::File::open(path)?.set_modified(later)?;
+    Ok(())
+}
+
+#[test]
+fn test_index_is_cached_and_invalidated() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+
+[workspace.dependencies]
+serde = "1.0"
+"#,
+    )?;
+    write(
+        &root.join("crates/base/Magnet.toml"),
+        r#"[package]
+name = "base"
+version = "0.1.0"
+"#,
+    )?;
+
+    let mut manager = ManifestManager::from_dir(root)?;
+    let index = manager.index()?;
+    assert_eq!(index.find_packages("base")[0].version, "0.1.0");
+    assert!(index.find_packages("app").is_empty());
+    assert_eq!(index.find_workspace_dependencies("serde").count(), 1);
+    assert!(!index.is_stale());
+
+    // Changed manifests are read again
+    write(
+        &root.join("crates/base/Magnet.toml"),
+        r#"[package]
+name = "base"
+version = "0.2.0"
+"#,
+    )?;
+    touch(&root.join("crates/base/Magnet.toml"))?;
+    let index = manager.index()?;
+    assert_eq!(index.find_packages("base")[0].version, "0.2.0");
+
+    // New packages are picked up
+    write(
+        &root.join("crates/app/Magnet.toml"),
+        r#"[package]
+name = "app"
+version = "0.1.0"
+"#,
+    )?;
+    touch(&root.join("crates"))?;
+    let index = manager.index()?;
+    assert_eq!(index.find_packages("app").len(), 1);
+    assert_eq!(index.packages().count(), 2);
+
+    // Edits to the root manifest itself are picked up
+    write(
+        &root.join("Magnet.toml"),
+        r#"[workspace]
+members = ["crates/base"]
+
+[workspace.dependencies]
+serde = "1.0"
+tokio = "1"
+"#,
+    )?;
+    touch(&root.join("Magnet.toml"))?;
+    let index = manager.index()?;
+    assert_eq!(index.find_workspace_dependencies("tokio").count(), 1);
+    assert!(index.find_packages("app").is_empty());
+    assert_eq!(index.packages().count(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_nested_nexus_discovery() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[nexus]
+name = "outer"
+members = ["inner", "tools"]
+"#,
+    )?;
+    write(
+        &root.join("inner/Magnet.toml"),
+        r#"[nexus]
+name = "inner"
+members = ["app"]
+"#,
+    )?;
+    write(
+        &root.join("inner/app/Magnet.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+"#,
+    )?;
+    write(
+        &root.join("inner/app/crates/core/Magnet.toml"),
+        r#"[package]
+name = "core"
+version = "0.1.0"
+"#,
+    )?;
+    write(
+        &root.join("tools/Magnet.toml"),
+        r#"[package]
+name = "tools"
+version = "0.1.0"
+
+[dependencies]
+core = { nexus = true }
+"#,
+    )?;
+
+    // Discovery from inside the inner nexus finds the outermost one
+    let (root_path, manifest) = magnet::utils::find_furthest_manifest(&root.join("inner/app"))?;
+    assert_eq!(root_path.canonicalize()?, root.canonicalize()?);
+    let ManifestModel::Nexus(nexus) = &manifest else {
+        panic!("expected the outer nexus, found {}", manifest.name());
+    };
+    assert_eq!(nexus.name, "outer");
+
+    let members = nexus.members()?;
+    assert_eq!(members.nexuses.len(), 1);
+    assert_eq!(members.nexuses[0].0.name, "inner");
+    assert_eq!(members.nexuses[0].1.workspaces.len(), 1);
+    let mut packages: Vec<String> = manifest
+        .list_packages()?
+        .into_iter()
+        .map(|package| package.name)
+        .collect();
+    packages.sort();
+    assert_eq!(packages, ["core", "tools"]);
+
+    // Packages of nested nexuses resolve through the outer nexus
+    let mut manager = ManifestManager::from_dir(root)?;
+    let mut tools = manager.index()?.find_packages("tools")[0].clone();
+    manager.resolve_package_dependencies(&mut tools)?;
+    assert_eq!(
+        tools.dependencies["core"].path.as_deref(),
+        Some(Path::new("../inner/app/crates/core"))
+    );
+
+    // A nexus listing its parent is a cycle
+    write(
+        &root.join("inner/Magnet.toml"),
+        r#"[nexus]
+name = "inner"
+members = ["app", ".."]
+"#,
+    )?;
+    let err = nexus.members().unwrap_err().to_string();
+    assert!(err.contains("Nexus cycle detected"), "{}", err);
+
+    // A directory can only belong to one nexus
+    write(
+        &root.join("inner/Magnet.toml"),
+        r#"[nexus]
+name = "inner"
+members = ["app", "../tools"]
+"#,
+    )?;
+    let err = nexus.members().unwrap_err().to_string();
+    assert!(err.contains("is a member of both nexus"), "{}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_nexus_package_disambiguation() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        r#"[nexus]
+name = "nexus"
+members = ["ws-a", "ws-b"]
+"#,
+    )?;
+    for workspace in ["ws-a", "ws-b"] {
+        write(
+            &root.join(workspace).join("Magnet.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )?;
+        write(
+            &root.join(workspace).join("crates/shared/Magnet.toml"),
+            "[package]\nname = \"shared\"\nversion = \"0.1.0\"\n",
+        )?;
+    }
+    let app_manifest = root.join("ws-a/crates/app/Magnet.toml");
+    write(
+        &app_manifest,
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+shared = { nexus = true }
+"#,
+    )?;
+
+    let resolve_shared = || -> Result<DependencyModel> {
+        let mut manager = ManifestManager::from_dir(root)?;
+        let mut app = manager.index()?.find_packages("app")[0].clone();
+        manager.resolve_package_dependencies(&mut app)?;
+        Ok(app.dependencies["shared"].clone())
+    };
+
+    // An ambiguous name lists the candidates and how to pick one
+    let err = resolve_shared().unwrap_err();
+    assert!(matches!(
+        MagnetError::find(&err),
+        Some(MagnetError::AmbiguousDependency { name, .. }) if name == "shared"
+    ));
+    let err = err.to_string();
+    assert!(err.contains("Multiple packages named 'shared'"), "{}", err);
+    assert!(
+        err.contains("ws-a/crates/shared (workspace 'ws-a')"),
+        "{}",
+        err
+    );
+    assert!(
+        err.contains("ws-b/crates/shared (workspace 'ws-b')"),
+        "{}",
+        err
+    );
+    assert!(err.contains(r#"shared = { nexus = "ws-a" }"#), "{}", err);
+
+    // The dependency can name the member workspace
+    write(
+        &app_manifest,
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+shared = { nexus = "ws-b" }
+"#,
+    )?;
+    let shared = resolve_shared()?;
+    assert_eq!(
+        shared.path.as_deref(),
+        Some(Path::new("../../../ws-b/crates/shared"))
+    );
+    assert!(shared.nexus.is_none());
+
+    // Or the nexus can prefer a workspace
+    write(
+        &app_manifest,
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+shared = { nexus = true }
+"#,
+    )?;
+    write(
+        &root.join("Magnet.toml"),
+        r#"[nexus]
+name = "nexus"
+members = ["ws-a", "ws-b"]
+priority = ["ws-b", "ws-a"]
+"#,
+    )?;
+    let shared = resolve_shared()?;
+    assert_eq!(
+        shared.path.as_deref(),
+        Some(Path::new("../../../ws-b/crates/shared"))
+    );
+
+    // Naming a workspace without the package is an error
+    write(
+        &app_manifest,
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+shared = { nexus = "ws-c" }
+"#,
+    )?;
+    let err = resolve_shared().unwrap_err().to_string();
+    assert!(err.contains("that workspace has no package"), "{}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_nexus_version_requirement() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        "[workspace]\nmembers = [\"crates/*\"]\n",
+    )?;
+    write(
+        &root.join("crates/shared/Magnet.toml"),
+        "[package]\nname = \"shared\"\nversion = \"1.4.0\"\n",
+    )?;
+    let app_manifest = root.join("crates/app/Magnet.toml");
+    let resolve_shared = |dependency: &str| -> Result<DependencyModel> {
+        write(
+            &app_manifest,
+            &format!(
+                "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nshared = {}\n",
+                dependency
+            ),
+        )?;
+        let mut manager = ManifestManager::from_dir(root)?;
+        let mut app = manager.index()?.find_packages("app")[0].clone();
+        manager.resolve_package_dependencies(&mut app)?;
+        Ok(app.dependencies["shared"].clone())
+    };
+
+    // A matching local package resolves as usual
+    let shared = resolve_shared(r#"{ version = "1.2", nexus = true }"#)?;
+    assert_eq!(shared.path.as_deref(), Some(Path::new("../shared")));
+
+    // A local package outside of the requirement is an error
+    let err = resolve_shared(r#"{ version = "^2", nexus = true }"#)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("requires version ^2"), "{}", err);
+    assert!(err.contains("is version 1.4.0"), "{}", err);
+
+    // Unless the dependency opts out, which is not passed on to Cargo
+    let shared = resolve_shared(r#"{ version = "^2", nexus = true, version-check = false }"#)?;
+    assert_eq!(shared.path.as_deref(), Some(Path::new("../shared")));
+    assert!(shared.version_check.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_strict_mode_and_fallback_chain() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path();
+    write(
+        &root.join("Magnet.toml"),
+        "[workspace]\nmembers = [\"crates/*\"]\n",
+    )?;
+    write(
+        &root.join("crates/shared/Magnet.toml"),
+        "[package]\nname = \"shared\"\nversion = \"1.4.0\"\n",
+    )?;
+    let app_manifest = root.join("crates/app/Magnet.toml");
+    let resolve = |name: &str, dependency: &str, strict: bool| -> Result<DependencyModel> {
+        write(
+            &app_manifest,
+            &format!(
+                "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\n{} = {}\n",
+                name, dependency
+            ),
+        )?;
+        let mut manager = ManifestManager::from_dir(root)?.with_strict(strict);
+        let mut app = manager.index()?.find_packages("app")[0].clone();
+        manager.resolve_package_dependencies(&mut app)?;
+        Ok(app.dependencies[name].clone())
+    };
+
+    // Unresolved dependencies are left as-is, unless strict
+    let missing = resolve("missing", "{ nexus = true }", false)?;
+    assert!(missing.nexus());
+    let err = resolve("missing", "{ nexus = true }", true).unwrap_err();
+    assert!(matches!(
+        MagnetError::find(&err),
+        Some(MagnetError::UnresolvedDependency { name, .. }) if name == "missing"
+    ));
+
+    // The first source of the chain that applies wins
+    let shared = resolve(
+        "shared",
+        r#"{ version = "1", fallback = ["nexus", "registry"] }"#,
+        true,
+    )?;
+    assert_eq!(shared.path.as_deref(), Some(Path::new("../shared")));
+    assert!(shared.fallback.is_none());
+
+    // A local package outside of the version requirement falls through to the registry
+    let shared = resolve(
+        "shared",
+        r#"{ version = "^2", fallback = ["nexus", "registry"] }"#,
+        true,
+    )?;
+    assert_eq!(shared.version.as_deref(), Some("^2"));
+    assert!(shared.path.is_none());
+
+    let missing = resolve(
+        "missing",
+        r#"{ git = "https://example.com/missing.git", fallback = ["nexus", "workspace", "registry", "git"] }"#,
+        true,
+    )?;
+    assert_eq!(
+        missing.git.as_deref(),
+        Some("https://example.com/missing.git")
+    );
+    assert!(missing.nexus.is_none());
+
+    // A chain where nothing applies is unresolved
+    let err = resolve("missing", r#"{ fallback = ["nexus", "registry"] }"#, true)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("could not be resolved from nexus, registry"),
+        "{}",
+        err
+    );
+
+    Ok(())
+}